@@ -12,21 +12,30 @@ if [ -z $TARGET ]; then
     exit 1
 fi
 
+# Every feature except `std`, which isn't available on bare metal targets
+FEATURES="graphics dirty-tiles eh02 async"
+
 cargo fmt --all -- --check
 
-cargo build --target $TARGET --all-features --release
+cargo build --target $TARGET --features "$FEATURES" --release
+
+# The `std` feature can only be built for the host
+cargo build --target x86_64-unknown-linux-gnu --all-features
 
 cargo test --lib --target x86_64-unknown-linux-gnu
+cargo test --lib --target x86_64-unknown-linux-gnu --all-features
 cargo test --doc --target x86_64-unknown-linux-gnu
+cargo test --doc --target x86_64-unknown-linux-gnu --features "$FEATURES"
+cargo test --doc --target x86_64-unknown-linux-gnu --features std
 
 if [ -z $DISABLE_EXAMPLES ]; then
-	cargo build --target $TARGET --all-features --examples
+	cargo build --target $TARGET --features "$FEATURES" --examples
 fi
 
 # Remove stale docs - the linkchecker might miss links to old files if they're not removed
 cargo clean --doc
 cargo clean --doc --target $TARGET
 
-cargo doc --all-features --target $TARGET
+cargo doc --features "$FEATURES" --target $TARGET
 
 linkchecker target/$TARGET/doc/ssd1331/index.html
@@ -17,7 +17,7 @@
 //! PB1 -> D/C
 //! ```
 //!
-//! Run on a Blue Pill with `cargo run --release --example image`.
+//! Run on a Blue Pill with `cargo run --release --example image --features eh02`.
 
 #![no_std]
 #![no_main]
@@ -25,7 +25,10 @@
 use cortex_m_rt::{entry, exception, ExceptionFrame};
 use embedded_graphics::{geometry::Point, image::Image, pixelcolor::Rgb565, prelude::*};
 use panic_semihosting as _;
-use ssd1331::{DisplayRotation, Ssd1331};
+use ssd1331::{
+    compat::{Eh02Delay, Eh02Pin, Eh02Spi},
+    DisplayRotation, Ssd1331,
+};
 use stm32f1xx_hal::{
     delay::Delay,
     prelude::*,
@@ -54,10 +57,10 @@ fn main() -> ! {
     let miso = gpioa.pa6;
     let mosi = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
 
-    let mut delay = Delay::new(cp.SYST, clocks);
+    let mut delay = Eh02Delay::new(Delay::new(cp.SYST, clocks));
 
-    let mut rst = gpiob.pb0.into_push_pull_output(&mut gpiob.crl);
-    let dc = gpiob.pb1.into_push_pull_output(&mut gpiob.crl);
+    let mut rst = Eh02Pin::new(gpiob.pb0.into_push_pull_output(&mut gpiob.crl));
+    let dc = Eh02Pin::new(gpiob.pb1.into_push_pull_output(&mut gpiob.crl));
 
     let spi = Spi::spi1(
         dp.SPI1,
@@ -72,7 +75,7 @@ fn main() -> ! {
         &mut rcc.apb2,
     );
 
-    let mut display = Ssd1331::new(spi, dc, DisplayRotation::Rotate0);
+    let mut display = Ssd1331::new(Eh02Spi::new(spi), dc, DisplayRotation::Rotate0);
 
     display.reset(&mut rst, &mut delay).unwrap();
     display.init().unwrap();
@@ -95,7 +98,9 @@ fn main() -> ! {
 
     display.flush().unwrap();
 
-    loop {}
+    loop {
+        cortex_m::asm::nop();
+    }
 }
 
 #[exception]
@@ -15,7 +15,7 @@
 //! PB1 -> D/C
 //! ```
 //!
-//! Run on a Blue Pill with `cargo run --release --example graphics`.
+//! Run on a Blue Pill with `cargo run --release --example graphics --features eh02`.
 
 #![no_std]
 #![no_main]
@@ -28,7 +28,11 @@ use embedded_graphics::{
     primitives::{Circle, PrimitiveStyle, Rectangle, Triangle},
 };
 use panic_semihosting as _;
-use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+use ssd1331::{
+    compat::{Eh02Delay, Eh02Pin, Eh02Spi},
+    DisplayRotation::Rotate0,
+    Ssd1331,
+};
 use stm32f1xx_hal::{
     delay::Delay,
     prelude::*,
@@ -56,10 +60,10 @@ fn main() -> ! {
     let miso = gpioa.pa6;
     let mosi = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
 
-    let mut delay = Delay::new(cp.SYST, clocks);
+    let mut delay = Eh02Delay::new(Delay::new(cp.SYST, clocks));
 
-    let mut rst = gpiob.pb0.into_push_pull_output(&mut gpiob.crl);
-    let dc = gpiob.pb1.into_push_pull_output(&mut gpiob.crl);
+    let mut rst = Eh02Pin::new(gpiob.pb0.into_push_pull_output(&mut gpiob.crl));
+    let dc = Eh02Pin::new(gpiob.pb1.into_push_pull_output(&mut gpiob.crl));
 
     let spi = Spi::spi1(
         dp.SPI1,
@@ -74,7 +78,7 @@ fn main() -> ! {
         &mut rcc.apb2,
     );
 
-    let mut display = Ssd1331::new(spi, dc, Rotate0);
+    let mut display = Ssd1331::new(Eh02Spi::new(spi), dc, Rotate0);
 
     display.reset(&mut rst, &mut delay).unwrap();
     display.init().unwrap();
@@ -101,7 +105,9 @@ fn main() -> ! {
 
     display.flush().unwrap();
 
-    loop {}
+    loop {
+        cortex_m::asm::nop();
+    }
 }
 
 #[exception]
@@ -0,0 +1,483 @@
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use display_interface_spi::SPIInterface;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
+
+use crate::{
+    colordepth::{Color256, Color65k, ColorDepth},
+    command::Command,
+    displayrotation::DisplayRotation,
+    error::Error,
+    framebuffer::Framebuffer,
+    gamma::GammaTable,
+    power::{DimConfig, DisplayPower},
+    BUF_SIZE, BUF_SIZE_256, DISPLAY_HEIGHT, DISPLAY_WIDTH,
+};
+
+/// SSD1331 display interface for async SPI devices
+///
+/// This is the async equivalent of [`Ssd1331`], available behind the `async` feature. Drawing
+/// into the framebuffer works exactly the same way, but sending commands and data to the display
+/// is done with `async` methods over an [`embedded-hal-async`] SPI device or any other
+/// [`display-interface`] `AsyncWriteOnlyDataCommand` implementation, so flushing the display
+/// doesn't block the executor.
+///
+/// The hardware acceleration and scrolling methods of [`Ssd1331`] are not available on this
+/// driver.
+///
+/// [`Ssd1331`]: ./struct.Ssd1331.html
+/// [`embedded-hal-async`]: https://docs.rs/embedded-hal-async
+/// [`display-interface`]: https://docs.rs/display-interface
+///
+/// # Examples
+///
+/// ## Draw a pixel and flush it to the display
+///
+/// ```rust
+/// # use ssd1331::test_helpers::{AsyncDelay, AsyncSpi, Pin};
+/// use ssd1331::{AsyncSsd1331, DisplayRotation::Rotate0};
+///
+/// async fn run(spi: AsyncSpi, dc: Pin, mut rst: Pin, mut delay: AsyncDelay) {
+///     let mut display = AsyncSsd1331::new(spi, dc, Rotate0);
+///
+///     display.reset(&mut rst, &mut delay).await.unwrap();
+///     display.init().await.unwrap();
+///
+///     display.set_pixel(10, 20, 0xffff);
+///
+///     display.flush().await.unwrap();
+/// }
+/// ```
+pub struct AsyncSsd1331<DI, B = [u8; BUF_SIZE], C = Color65k> {
+    /// Framebuffer, dirty area and rotation
+    fb: Framebuffer<B, C>,
+
+    /// Display interface
+    iface: DI,
+}
+
+impl<SPI, DC> AsyncSsd1331<SPIInterface<SPI, DC>>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Create new display instance
+    ///
+    /// Ensure `display.init()` is called before sending data otherwise nothing will be shown.
+    pub fn new(spi: SPI, dc: DC, display_rotation: DisplayRotation) -> Self {
+        Self::with_buffer(spi, dc, display_rotation, [0; BUF_SIZE])
+    }
+}
+
+impl<SPI, DC> AsyncSsd1331<SPIInterface<SPI, DC>, [u8; BUF_SIZE_256], Color256>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Create new display instance in 256 colour mode
+    ///
+    /// See [`Ssd1331::new_256_color`] for details.
+    ///
+    /// [`Ssd1331::new_256_color`]: ./struct.Ssd1331.html#method.new_256_color
+    pub fn new_256_color(spi: SPI, dc: DC, display_rotation: DisplayRotation) -> Self {
+        Self::with_buffer_256_color(spi, dc, display_rotation, [0; BUF_SIZE_256])
+    }
+}
+
+impl<SPI, DC, B> AsyncSsd1331<SPIInterface<SPI, DC>, B>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance using the given storage for the framebuffer
+    ///
+    /// See [`Ssd1331::with_buffer`] for details.
+    ///
+    /// [`Ssd1331::with_buffer`]: ./struct.Ssd1331.html#method.with_buffer
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE`] bytes.
+    ///
+    /// [`BUF_SIZE`]: ./constant.BUF_SIZE.html
+    pub fn with_buffer(spi: SPI, dc: DC, display_rotation: DisplayRotation, buffer: B) -> Self {
+        Self::from_interface(SPIInterface::new(spi, dc), display_rotation, buffer)
+    }
+}
+
+impl<SPI, DC, B> AsyncSsd1331<SPIInterface<SPI, DC>, B, Color256>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance in 256 colour mode using the given storage for the framebuffer
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE_256`] bytes.
+    ///
+    /// [`BUF_SIZE_256`]: ./constant.BUF_SIZE_256.html
+    pub fn with_buffer_256_color(
+        spi: SPI,
+        dc: DC,
+        display_rotation: DisplayRotation,
+        buffer: B,
+    ) -> Self {
+        Self::from_interface_256_color(SPIInterface::new(spi, dc), display_rotation, buffer)
+    }
+}
+
+impl<DI, B> AsyncSsd1331<DI, B>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance using the given display interface and framebuffer storage
+    ///
+    /// See [`Ssd1331::from_interface`] for details.
+    ///
+    /// [`Ssd1331::from_interface`]: ./struct.Ssd1331.html#method.from_interface
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE`] bytes.
+    ///
+    /// [`BUF_SIZE`]: ./constant.BUF_SIZE.html
+    pub fn from_interface(iface: DI, display_rotation: DisplayRotation, buffer: B) -> Self {
+        Self::from_parts(iface, display_rotation, buffer)
+    }
+}
+
+impl<DI, B> AsyncSsd1331<DI, B, Color256>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance in 256 colour mode using the given display interface and
+    /// framebuffer storage
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE_256`] bytes.
+    ///
+    /// [`BUF_SIZE_256`]: ./constant.BUF_SIZE_256.html
+    pub fn from_interface_256_color(
+        iface: DI,
+        display_rotation: DisplayRotation,
+        buffer: B,
+    ) -> Self {
+        Self::from_parts(iface, display_rotation, buffer)
+    }
+}
+
+impl<DI, B, C> AsyncSsd1331<DI, B, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    /// Create new display instance from its parts, checking the buffer is large enough
+    fn from_parts(iface: DI, display_rotation: DisplayRotation, buffer: B) -> Self {
+        Self {
+            iface,
+            fb: Framebuffer::new(display_rotation, buffer),
+        }
+    }
+
+    /// Release the display interface for reuse in other code
+    pub fn release(self) -> DI {
+        self.iface
+    }
+
+    /// Clear the display buffer
+    ///
+    /// `display.flush()` must be called to update the display
+    pub fn clear(&mut self) {
+        self.fb.clear();
+    }
+
+    /// Reset the display
+    ///
+    /// This method brings the RST pin low for 1ms to reset the module,  waits for another 1ms then
+    /// brings RST high
+    pub async fn reset<RST, DELAY, PinE>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<(), PinE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DELAY: DelayNs,
+    {
+        rst.set_high().map_err(Error::Pin)?;
+        delay.delay_ms(1).await;
+        rst.set_low().map_err(Error::Pin)?;
+        delay.delay_ms(1).await;
+        rst.set_high().map_err(Error::Pin)?;
+
+        Ok(())
+    }
+
+    /// Send the full framebuffer to the display
+    ///
+    /// This resets the draw area the full size of the display
+    pub async fn flush(&mut self) -> Result<(), DisplayError> {
+        // Ensure the display buffer is at the origin of the display before we send the full frame
+        // to prevent accidental offsets
+        self.set_draw_area((0, 0), (DISPLAY_WIDTH - 1, DISPLAY_HEIGHT - 1))
+            .await?;
+
+        self.iface
+            .send_data(DataFormat::U8(self.fb.bytes()))
+            .await?;
+
+        self.fb.dirty.reset();
+
+        Ok(())
+    }
+
+    /// Send only the area of the framebuffer changed since the last flush to the display
+    ///
+    /// See [`Ssd1331::flush_dirty`] for details.
+    ///
+    /// [`Ssd1331::flush_dirty`]: ./struct.Ssd1331.html#method.flush_dirty
+    pub async fn flush_dirty(&mut self) -> Result<(), DisplayError> {
+        for (start, end) in self.fb.dirty.take() {
+            self.flush_area(start, end).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a rectangular area of the framebuffer to the display
+    ///
+    /// `start` must be the top left and `end` the bottom right corner of the area, both of which
+    /// must lie within the display.
+    async fn flush_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
+        let (gram_start, gram_end) = match self.fb.display_rotation.gram_area(start, end) {
+            Some(area) => area,
+            None => return Ok(()),
+        };
+
+        self.set_draw_area(gram_start, gram_end).await?;
+
+        for chunk in self.fb.area_chunks(start, end) {
+            self.iface.send_data(DataFormat::U8(chunk)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the top left and bottom right corners of a bounding box to draw to
+    pub async fn set_draw_area(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+    ) -> Result<(), DisplayError> {
+        Command::ColumnAddress(start.0, end.0)
+            .send_async(&mut self.iface)
+            .await?;
+        Command::RowAddress(start.1, end.1)
+            .send_async(&mut self.iface)
+            .await?;
+        Ok(())
+    }
+
+    /// Set the value for an individual pixel.
+    ///
+    /// The value is a raw RGB565 `u16` in the default 65k colour mode, or a raw RGB332 `u8` in 256
+    /// colour mode.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: C::Raw) {
+        self.fb.set_pixel(x, y, value);
+    }
+
+    /// Get the value of an individual pixel from the framebuffer
+    ///
+    /// Coordinates are given in the same rotated coordinate space as [`set_pixel`]. The value is a
+    /// raw RGB565 `u16` in the default 65k colour mode, or a raw RGB332 `u8` in 256 colour mode.
+    /// Returns `None` if the pixel lies outside the display.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<C::Raw> {
+        self.fb.get_pixel(x, y)
+    }
+
+    /// Initialise display, setting sensible defaults and rotation
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        for command in Command::init_sequence(self.fb.display_rotation, C::COLOR_MODE) {
+            command.send_async(&mut self.iface).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn dimensions(&self) -> (u8, u8) {
+        self.fb.dimensions()
+    }
+
+    /// Set the display rotation
+    pub async fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DisplayError> {
+        self.fb.set_rotation(rot);
+
+        rot.remap_command(C::COLOR_MODE)
+            .send_async(&mut self.iface)
+            .await
+    }
+
+    /// Get the current rotation of the display
+    pub fn rotation(&self) -> DisplayRotation {
+        self.fb.display_rotation
+    }
+
+    /// Turn the display on (eg exiting sleep mode)
+    pub async fn turn_on(&mut self) -> Result<(), DisplayError> {
+        Command::DisplayOn(true).send_async(&mut self.iface).await
+    }
+
+    /// Turn the display off (enter sleep mode)
+    pub async fn turn_off(&mut self) -> Result<(), DisplayError> {
+        Command::DisplayOn(false).send_async(&mut self.iface).await
+    }
+
+    /// Switch the display off, on in dim mode or on at normal brightness
+    ///
+    /// See [`Ssd1331::set_power_mode`] for details.
+    ///
+    /// [`Ssd1331::set_power_mode`]: ./struct.Ssd1331.html#method.set_power_mode
+    pub async fn set_power_mode(&mut self, mode: DisplayPower) -> Result<(), DisplayError> {
+        mode.command().send_async(&mut self.iface).await
+    }
+
+    /// Set the contrast and pre-charge voltage used when the display is in
+    /// [`DisplayPower::Dim`] mode
+    ///
+    /// See [`Ssd1331::set_dim_config`] for details.
+    ///
+    /// [`DisplayPower::Dim`]: ./enum.DisplayPower.html#variant.Dim
+    /// [`Ssd1331::set_dim_config`]: ./struct.Ssd1331.html#method.set_dim_config
+    pub async fn set_dim_config(&mut self, config: DimConfig) -> Result<(), DisplayError> {
+        config.command().send_async(&mut self.iface).await
+    }
+
+    /// Set the gray scale table used to map pixel values to brightness
+    ///
+    /// See [`Ssd1331::set_gamma`] for details.
+    ///
+    /// [`Ssd1331::set_gamma`]: ./struct.Ssd1331.html#method.set_gamma
+    pub async fn set_gamma(&mut self, table: GammaTable) -> Result<(), DisplayError> {
+        table.command().send_async(&mut self.iface).await
+    }
+
+    /// Set the overall brightness of the display from 0 (dimmest) to 15 (brightest)
+    ///
+    /// See [`Ssd1331::set_brightness`] for details.
+    ///
+    /// [`Ssd1331::set_brightness`]: ./struct.Ssd1331.html#method.set_brightness
+    pub async fn set_brightness(&mut self, level: u8) -> Result<(), DisplayError> {
+        Command::MasterCurrent(level)
+            .send_async(&mut self.iface)
+            .await
+    }
+
+    /// Set the contrast of the red, green and blue channels
+    ///
+    /// See [`Ssd1331::set_contrast`] for details.
+    ///
+    /// [`Ssd1331::set_contrast`]: ./struct.Ssd1331.html#method.set_contrast
+    pub async fn set_contrast(&mut self, r: u8, g: u8, b: u8) -> Result<(), DisplayError> {
+        Command::Contrast(b, g, r).send_async(&mut self.iface).await
+    }
+
+    /// Send a low level command to the display
+    ///
+    /// See [`Ssd1331::send_command`] for details.
+    ///
+    /// [`Ssd1331::send_command`]: ./struct.Ssd1331.html#method.send_command
+    pub async fn send_command(&mut self, command: Command) -> Result<(), DisplayError> {
+        command.send_async(&mut self.iface).await
+    }
+
+    /// Send raw bytes to the display in command mode
+    ///
+    /// See [`Ssd1331::send_bytes`] for details.
+    ///
+    /// [`Ssd1331::send_bytes`]: ./struct.Ssd1331.html#method.send_bytes
+    pub async fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), DisplayError> {
+        self.iface.send_commands(DataFormat::U8(bytes)).await
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Point, Size},
+    image::GetPixel,
+    primitives::Rectangle,
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> DrawTarget for AsyncSsd1331<DI, B, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    type Color = C::Color;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.fb.draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.fb.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fb.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        DrawTarget::clear(&mut self.fb, color)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> GetPixel for AsyncSsd1331<DI, B, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    type Color = C::Color;
+
+    fn pixel(&self, p: Point) -> Option<Self::Color> {
+        if p.x < 0 || p.y < 0 {
+            return None;
+        }
+
+        self.get_pixel(p.x as u32, p.y as u32).map(C::raw_to_color)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> OriginDimensions for AsyncSsd1331<DI, B, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    fn size(&self) -> Size {
+        self.fb.size()
+    }
+}
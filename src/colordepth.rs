@@ -0,0 +1,161 @@
+use crate::{command::ColorMode, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+#[cfg(feature = "graphics")]
+use crate::rgb332::Rgb332;
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::pixelcolor::{
+    raw::{RawData, RawU16, RawU8},
+    PixelColor, Rgb565,
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Colour depth used by the display and its framebuffer
+///
+/// This trait is implemented by [`Color65k`] and [`Color256`] and cannot be implemented outside
+/// this crate.
+///
+/// [`Color65k`]: ./struct.Color65k.html
+/// [`Color256`]: ./struct.Color256.html
+pub trait ColorDepth: private::Sealed {
+    /// Raw pixel value type, as accepted by `set_pixel`
+    type Raw: Copy + Default;
+
+    /// `embedded-graphics` colour type used when drawing
+    #[cfg(feature = "graphics")]
+    type Color: PixelColor + Into<Rgb565>;
+
+    /// Number of bytes used to store each pixel
+    const BYTES_PER_PIXEL: usize;
+
+    /// Size of a full framebuffer in bytes
+    const BUF_SIZE: usize =
+        DISPLAY_WIDTH as usize * DISPLAY_HEIGHT as usize * Self::BYTES_PER_PIXEL;
+
+    #[doc(hidden)]
+    const COLOR_MODE: ColorMode;
+
+    #[doc(hidden)]
+    fn write_raw(raw: Self::Raw, bytes: &mut [u8]);
+
+    #[doc(hidden)]
+    fn read_raw(bytes: &[u8]) -> Self::Raw;
+
+    #[doc(hidden)]
+    #[cfg(feature = "std")]
+    fn raw_to_rgb888(raw: Self::Raw) -> [u8; 3];
+
+    #[doc(hidden)]
+    #[cfg(feature = "graphics")]
+    fn color_to_raw(color: Self::Color) -> Self::Raw;
+
+    #[doc(hidden)]
+    #[cfg(feature = "graphics")]
+    fn raw_to_color(raw: Self::Raw) -> Self::Color;
+}
+
+/// Scale a colour channel value with the given maximum to 8 bits, rounding to the nearest value
+#[cfg(feature = "std")]
+fn scale_channel(value: u16, max: u16) -> u8 {
+    ((value * 255 + max / 2) / max) as u8
+}
+
+/// 65k colour mode with 16 bits per pixel
+///
+/// Pixels are stored as RGB565 values. This is the default colour depth.
+#[derive(Debug, Clone, Copy)]
+pub struct Color65k;
+
+impl private::Sealed for Color65k {}
+
+impl ColorDepth for Color65k {
+    type Raw = u16;
+
+    #[cfg(feature = "graphics")]
+    type Color = Rgb565;
+
+    const BYTES_PER_PIXEL: usize = 2;
+
+    const COLOR_MODE: ColorMode = ColorMode::CM65k;
+
+    fn write_raw(raw: Self::Raw, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&raw.to_be_bytes());
+    }
+
+    fn read_raw(bytes: &[u8]) -> Self::Raw {
+        u16::from_be_bytes([bytes[0], bytes[1]])
+    }
+
+    #[cfg(feature = "std")]
+    fn raw_to_rgb888(raw: Self::Raw) -> [u8; 3] {
+        [
+            scale_channel(raw >> 11, 0x1f),
+            scale_channel(raw >> 5 & 0x3f, 0x3f),
+            scale_channel(raw & 0x1f, 0x1f),
+        ]
+    }
+
+    #[cfg(feature = "graphics")]
+    fn color_to_raw(color: Self::Color) -> Self::Raw {
+        RawU16::from(color).into_inner()
+    }
+
+    #[cfg(feature = "graphics")]
+    fn raw_to_color(raw: Self::Raw) -> Self::Color {
+        RawU16::new(raw).into()
+    }
+}
+
+/// 256 colour mode with 8 bits per pixel
+///
+/// Pixels are stored as [`Rgb332`] values, halving the size of the framebuffer and the amount of data
+/// sent to the display compared to [`Color65k`].
+///
+/// [`Rgb332`]: ./struct.Rgb332.html
+/// [`Color65k`]: ./struct.Color65k.html
+#[derive(Debug, Clone, Copy)]
+pub struct Color256;
+
+impl private::Sealed for Color256 {}
+
+impl ColorDepth for Color256 {
+    type Raw = u8;
+
+    #[cfg(feature = "graphics")]
+    type Color = Rgb332;
+
+    const BYTES_PER_PIXEL: usize = 1;
+
+    const COLOR_MODE: ColorMode = ColorMode::CM256;
+
+    fn write_raw(raw: Self::Raw, bytes: &mut [u8]) {
+        bytes[0] = raw;
+    }
+
+    fn read_raw(bytes: &[u8]) -> Self::Raw {
+        bytes[0]
+    }
+
+    #[cfg(feature = "std")]
+    fn raw_to_rgb888(raw: Self::Raw) -> [u8; 3] {
+        let raw = u16::from(raw);
+
+        [
+            scale_channel(raw >> 5, 0x7),
+            scale_channel(raw >> 2 & 0x7, 0x7),
+            scale_channel(raw & 0x3, 0x3),
+        ]
+    }
+
+    #[cfg(feature = "graphics")]
+    fn color_to_raw(color: Self::Color) -> Self::Raw {
+        RawU8::from(color).into_inner()
+    }
+
+    #[cfg(feature = "graphics")]
+    fn raw_to_color(raw: Self::Raw) -> Self::Color {
+        RawU8::new(raw).into()
+    }
+}
@@ -1,13 +1,21 @@
+//! SSD1331 command set
+//!
+//! Each [`Command`] encodes one of the commands listed in the "Command Table" section of the
+//! SSD1331 datasheet, along with its parameters.
+//!
+//! [`Command`]: ./enum.Command.html
+
 // Shamefully taken from https://github.com/EdgewaterDevelopment/rust-ssd1331
 
-use crate::error::Error;
-use embedded_hal::digital::v2::OutputPin;
+use crate::{displayrotation::DisplayRotation, DISPLAY_HEIGHT};
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
 
 /// SSD1331 Commands
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum Command {
-    /// Set (r, g, b) contrast. Higher number is higher contrast.
+    /// Set contrast of colour A, B and C, which are blue, green and red respectively. Higher number
+    /// is higher contrast.
     Contrast(u8, u8, u8),
     /// Turn entire display on. If set, all pixels will
     /// be set to on, if not, the value in memory will be used.
@@ -41,87 +49,337 @@ pub enum Command {
     /// First value is oscillator frequency, increasing with higher value
     /// Second value is divide ratio - 1
     DisplayClockDiv(u8, u8),
-    /// Set up phase 1 and 2 of precharge period. each value is from 0-63
+    /// Set up phase 1 and 2 of precharge period in DCLKs. Each value is from 1-15 and is clamped
+    /// to that range.
     PreChargePeriod(u8, u8),
     /// Set Vcomh Deselect level
     VcomhDeselect(VcomhLevel),
     /// NOOP
     Noop,
+    /// Draw a line in display RAM using the controller's graphics acceleration
+    ///
+    /// Coordinates are `(column, row)` GRAM addresses. Colour is a raw RGB565 value.
+    DrawLine {
+        /// Start point of the line
+        start: (u8, u8),
+        /// End point of the line
+        end: (u8, u8),
+        /// Line colour as a raw RGB565 value
+        color: u16,
+    },
+    /// Draw a rectangle in display RAM using the controller's graphics acceleration
+    ///
+    /// Coordinates are `(column, row)` GRAM addresses. Colours are raw RGB565 values. The fill
+    /// colour is only used if fill is enabled with [`Command::Fill`].
+    DrawRect {
+        /// Top left corner of the rectangle
+        start: (u8, u8),
+        /// Bottom right corner of the rectangle
+        end: (u8, u8),
+        /// Outline colour as a raw RGB565 value
+        outline: u16,
+        /// Fill colour as a raw RGB565 value
+        fill: u16,
+    },
+    /// Copy a rectangular region of display RAM to another location
+    ///
+    /// Coordinates are `(column, row)` GRAM addresses.
+    Copy {
+        /// Top left corner of the source region
+        start: (u8, u8),
+        /// Bottom right corner of the source region
+        end: (u8, u8),
+        /// Top left corner of the destination
+        dest: (u8, u8),
+    },
+    /// Dim a rectangular region of display RAM
+    ///
+    /// Coordinates are `(column, row)` GRAM addresses.
+    DimWindow {
+        /// Top left corner of the region
+        start: (u8, u8),
+        /// Bottom right corner of the region
+        end: (u8, u8),
+    },
+    /// Clear a rectangular region of display RAM to black
+    ///
+    /// Coordinates are `(column, row)` GRAM addresses.
+    ClearWindow {
+        /// Top left corner of the region
+        start: (u8, u8),
+        /// Bottom right corner of the region
+        end: (u8, u8),
+    },
+    /// Set up continuous horizontal and/or vertical scrolling
+    ScrollSetup {
+        /// Number of columns to scroll horizontally each step, from 0-95. 0 disables horizontal
+        /// scrolling.
+        horizontal_offset: u8,
+        /// First row to scroll horizontally
+        start_row: u8,
+        /// Number of rows to scroll horizontally
+        rows: u8,
+        /// Number of rows to scroll vertically each step, from 0-63. 0 disables vertical scrolling.
+        vertical_offset: u8,
+        /// Time interval between each scroll step
+        interval: NFrames,
+    },
+    /// Activate (true) or deactivate (false) scrolling
+    Scroll(bool),
+    /// Enable or disable filling of rectangles drawn with [`Command::DrawRect`] (first value) and
+    /// reversal of colours during window copies (second value)
+    Fill(bool, bool),
+    /// Set master current attenuation factor from 0-15, giving a segment current of `(n + 1) / 16`
+    /// of the maximum. Larger values are clamped to 15.
+    MasterCurrent(u8),
+    /// Set second pre-charge speed for colour A, B and C
+    ///
+    /// These should initially be set to the same values as the contrast of each colour.
+    SecondPrechargeSpeed(u8, u8, u8),
+    /// Set the pulse widths of the odd gray scale levels GS1, GS3, ..., GS63
+    GrayScaleTable(GrayScaleTable),
+    /// Reset the gray scale table to the built in linear table
+    LinearGrayScaleTable,
+    /// Enable (true) or disable (false) power save mode
+    PowerSave(bool),
+    /// Set pre-charge voltage level from 0-31, where 0 is 0.1 * Vcc and 31 is 0.5 * Vcc. Larger
+    /// values are clamped to 31.
+    PreChargeLevel(u8),
+    /// Configure the contrast and pre-charge voltage used in dim mode
+    DimModeSetting {
+        /// Contrast of colour A
+        contrast_a: u8,
+        /// Contrast of colour B
+        contrast_b: u8,
+        /// Contrast of colour C
+        contrast_c: u8,
+        /// Pre-charge voltage level from 0-31, as for [`Command::PreChargeLevel`]. Larger values
+        /// are clamped to 31.
+        precharge: u8,
+    },
+    /// Turn display on in dim mode, using the settings from [`Command::DimModeSetting`]
+    DisplayOnDim,
+    /// Lock (true) or unlock (false) the command interface. While locked, all commands and memory
+    /// access except unlocking are ignored.
+    CommandLock(bool),
 }
 
 impl Command {
+    /// Commands sent to initialise the display, setting sensible defaults and the given rotation
+    /// and colour mode
+    pub(crate) fn init_sequence(
+        display_rotation: DisplayRotation,
+        color_mode: ColorMode,
+    ) -> [Command; 12] {
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(0xF, 0x0),
+            Command::Multiplex(DISPLAY_HEIGHT - 1),
+            Command::StartLine(0),
+            Command::DisplayOffset(0),
+            display_rotation.remap_command(color_mode),
+            // Values taken from [here](https://github.com/adafruit/Adafruit-SSD1331-OLED-Driver-Library-for-Arduino/blob/master/Adafruit_SSD1331.cpp#L119-L124)
+            Command::Contrast(0x91, 0x50, 0x7D),
+            Command::PreChargePeriod(0x1, 0x3),
+            Command::VcomhDeselect(VcomhLevel::V071),
+            Command::AllOn(false),
+            Command::Invert(false),
+            Command::DisplayOn(true),
+        ]
+    }
+
     /// Send command to SSD1331
-    pub fn send<SPI, DC, CommE, PinE>(
-        self,
-        spi: &mut SPI,
-        dc: &mut DC,
-    ) -> Result<(), Error<CommE, PinE>>
+    pub fn send<DI>(self, iface: &mut DI) -> Result<(), DisplayError>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let (data, len) = self.encode();
+
+        // The SSD1331 expects command parameters to be sent in command mode too
+        iface.send_commands(DataFormat::U8(&data[0..len]))
+    }
+
+    /// Send command to SSD1331 over an async display interface
+    #[cfg(feature = "async")]
+    pub async fn send_async<DI>(self, iface: &mut DI) -> Result<(), DisplayError>
     where
-        SPI: hal::blocking::spi::Write<u8, Error = CommE>,
-        DC: OutputPin<Error = PinE>,
+        DI: display_interface::AsyncWriteOnlyDataCommand,
     {
-        // Transform command into a fixed size array of 7 u8 and the real length for sending
-        let (data, len) = match self {
-            Command::Contrast(a, b, c) => ([0x81, a, 0x82, b, 0x83, c, 0], 6),
+        let (data, len) = self.encode();
+
+        // The SSD1331 expects command parameters to be sent in command mode too
+        iface.send_commands(DataFormat::U8(&data[0..len])).await
+    }
+
+    /// Transform command into a fixed size array of u8 and the real length for sending
+    fn encode(self) -> ([u8; MAX_COMMAND_LEN], usize) {
+        match self {
+            Command::Contrast(a, b, c) => pack(&[0x81, a, 0x82, b, 0x83, c]),
             // TODO: Collapse AllOn and Invert commands into new DisplayMode cmd with enum
-            Command::AllOn(on) => ([if on { 0xA5 } else { 0xA6 }, 0, 0, 0, 0, 0, 0], 1),
-            Command::Invert(inv) => ([if inv { 0xA7 } else { 0xA4 }, 0, 0, 0, 0, 0, 0], 1),
-            Command::DisplayOn(on) => ([0xAE | (on as u8), 0, 0, 0, 0, 0, 0], 1),
-            Command::ColumnAddress(start, end) => ([0x15, start, end, 0, 0, 0, 0], 3),
-            Command::RowAddress(start, end) => ([0x75, start, end, 0, 0, 0, 0], 3),
-            Command::StartLine(line) => ([0xA1, (0x3F & line), 0, 0, 0, 0, 0], 2),
-            Command::RemapAndColorDepth(hremap, vremap, cmode, addr_inc_mode) => (
-                [
-                    0xA0,
-                    0x20 | ((vremap as u8) << 4
-                        | (hremap as u8) << 1
-                        | (cmode as u8) << 6
-                        | (addr_inc_mode as u8)),
-                    0,
-                    0,
-                    0,
-                    0,
-                    0,
-                ],
-                2,
-            ),
-            Command::Multiplex(ratio) => ([0xA8, ratio, 0, 0, 0, 0, 0], 2),
-            Command::ReverseComDir(rev) => ([0xC0 | ((rev as u8) << 3), 0, 0, 0, 0, 0, 0], 1),
-            Command::DisplayOffset(offset) => ([0xA2, offset, 0, 0, 0, 0, 0], 2),
-            Command::ComPinConfig(alt, lr) => (
-                [
-                    0xDA,
-                    0x2 | ((alt as u8) << 4) | ((lr as u8) << 5),
-                    0,
-                    0,
-                    0,
-                    0,
-                    0,
-                ],
-                2,
-            ),
-            Command::DisplayClockDiv(fosc, div) => {
-                ([0xB3, ((0xF & fosc) << 4) | (0xF & div), 0, 0, 0, 0, 0], 2)
+            Command::AllOn(on) => pack(&[if on { 0xA5 } else { 0xA6 }]),
+            Command::Invert(inv) => pack(&[if inv { 0xA7 } else { 0xA4 }]),
+            Command::DisplayOn(on) => pack(&[0xAE | (on as u8)]),
+            Command::ColumnAddress(start, end) => pack(&[0x15, start, end]),
+            Command::RowAddress(start, end) => pack(&[0x75, start, end]),
+            Command::StartLine(line) => pack(&[0xA1, (0x3F & line)]),
+            Command::RemapAndColorDepth(hremap, vremap, cmode, addr_inc_mode) => pack(&[
+                0xA0,
+                0x20 | ((vremap as u8) << 4
+                    | (hremap as u8) << 1
+                    | (cmode as u8) << 6
+                    | (addr_inc_mode as u8)),
+            ]),
+            Command::Multiplex(ratio) => pack(&[0xA8, ratio]),
+            Command::ReverseComDir(rev) => pack(&[0xC0 | ((rev as u8) << 3)]),
+            Command::DisplayOffset(offset) => pack(&[0xA2, offset]),
+            Command::ComPinConfig(alt, lr) => {
+                pack(&[0xDA, 0x2 | ((alt as u8) << 4) | ((lr as u8) << 5)])
             }
-            Command::PreChargePeriod(phase1, phase2) => (
-                [0x3e, ((0xF & phase2) << 4) | (0xF & phase1), 0, 0, 0, 0, 0],
-                2,
-            ),
-            Command::VcomhDeselect(level) => ([0xBE, (level as u8) << 1, 0, 0, 0, 0, 0], 2),
-            Command::Noop => ([0xE3, 0, 0, 0, 0, 0, 0], 1),
-        };
+            Command::DisplayClockDiv(fosc, div) => pack(&[0xB3, ((0xF & fosc) << 4) | (0xF & div)]),
+            Command::PreChargePeriod(phase1, phase2) => {
+                pack(&[0xB1, (phase2.clamp(1, 15) << 4) | phase1.clamp(1, 15)])
+            }
+            Command::VcomhDeselect(level) => pack(&[0xBE, (level as u8) << 1]),
+            Command::Noop => pack(&[0xE3]),
+            Command::DrawLine { start, end, color } => {
+                let (c, b, a) = color_components(color);
+
+                pack(&[0x21, start.0, start.1, end.0, end.1, c, b, a])
+            }
+            Command::DrawRect {
+                start,
+                end,
+                outline,
+                fill,
+            } => {
+                let (oc, ob, oa) = color_components(outline);
+                let (fc, fb, fa) = color_components(fill);
+
+                pack(&[0x22, start.0, start.1, end.0, end.1, oc, ob, oa, fc, fb, fa])
+            }
+            Command::Copy { start, end, dest } => {
+                pack(&[0x23, start.0, start.1, end.0, end.1, dest.0, dest.1])
+            }
+            Command::DimWindow { start, end } => pack(&[0x24, start.0, start.1, end.0, end.1]),
+            Command::ClearWindow { start, end } => pack(&[0x25, start.0, start.1, end.0, end.1]),
+            Command::ScrollSetup {
+                horizontal_offset,
+                start_row,
+                rows,
+                vertical_offset,
+                interval,
+            } => pack(&[
+                0x27,
+                horizontal_offset,
+                start_row,
+                rows,
+                vertical_offset,
+                interval as u8,
+            ]),
+            Command::Scroll(on) => pack(&[0x2E | (on as u8)]),
+            Command::Fill(fill, reverse) => pack(&[0x26, (fill as u8) | ((reverse as u8) << 4)]),
+            Command::MasterCurrent(current) => pack(&[0x87, current.min(15)]),
+            Command::SecondPrechargeSpeed(a, b, c) => pack(&[0x8A, a, 0x8B, b, 0x8C, c]),
+            Command::GrayScaleTable(table) => {
+                let mut data = [0; MAX_COMMAND_LEN];
 
-        // Command mode. 1 = data, 0 = command
-        dc.set_low().map_err(Error::Pin)?;
+                data[0] = 0xB8;
+                data[1..].copy_from_slice(&table.0);
 
-        // Send command over the interface
-        spi.write(&data[0..len]).map_err(Error::Comm)
+                (data, MAX_COMMAND_LEN)
+            }
+            Command::LinearGrayScaleTable => pack(&[0xB9]),
+            Command::PowerSave(on) => pack(&[0xB0, if on { 0x1A } else { 0x0B }]),
+            Command::PreChargeLevel(level) => pack(&[0xBB, level.min(31) << 1]),
+            Command::DimModeSetting {
+                contrast_a,
+                contrast_b,
+                contrast_c,
+                precharge,
+            } => pack(&[
+                0xAB,
+                0x00,
+                contrast_a,
+                contrast_b,
+                contrast_c,
+                precharge.min(31),
+            ]),
+            Command::DisplayOnDim => pack(&[0xAC]),
+            Command::CommandLock(lock) => pack(&[0xFD, if lock { 0x16 } else { 0x12 }]),
+        }
+    }
+}
+
+/// Maximum number of bytes (including the command byte itself) sent by a single command
+///
+/// The longest command is [`Command::GrayScaleTable`] with 32 parameters.
+const MAX_COMMAND_LEN: usize = 33;
+
+/// Copy a command into a fixed size array, returning it along with the number of bytes to send
+fn pack(bytes: &[u8]) -> ([u8; MAX_COMMAND_LEN], usize) {
+    let mut data = [0; MAX_COMMAND_LEN];
+
+    data[0..bytes.len()].copy_from_slice(bytes);
+
+    (data, bytes.len())
+}
+
+/// Split a raw RGB565 value into the 6 bit colour C, B and A components used by the graphics
+/// acceleration commands
+///
+/// The 5 bit red and blue channels are shifted into bits `[5:1]` as required by the datasheet.
+fn color_components(color: u16) -> (u8, u8, u8) {
+    let r = ((color >> 11) & 0x1f) as u8;
+    let g = ((color >> 5) & 0x3f) as u8;
+    let b = (color & 0x1f) as u8;
+
+    (r << 1, g, b << 1)
+}
+
+/// Gray scale table of pulse widths, in DCLKs, for the odd gray scale levels GS1, GS3, ..., GS63
+///
+/// The display derives the even levels by averaging their neighbours. Level GS0 is always off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrayScaleTable([u8; 32]);
+
+impl GrayScaleTable {
+    /// Maximum pulse width of any level
+    pub const MAX_PULSE_WIDTH: u8 = 125;
+
+    /// Create a gray scale table from the pulse widths of GS1, GS3, ..., GS63
+    ///
+    /// Returns `None` unless the table meets the datasheet's requirements: the first pulse width
+    /// must be greater than 0, each pulse width must be at least 2 greater than the one before it
+    /// and none may be greater than [`MAX_PULSE_WIDTH`].
+    ///
+    /// [`MAX_PULSE_WIDTH`]: #associatedconstant.MAX_PULSE_WIDTH
+    pub const fn new(pulse_widths: [u8; 32]) -> Option<Self> {
+        if pulse_widths[0] == 0 || pulse_widths[31] > Self::MAX_PULSE_WIDTH {
+            return None;
+        }
+
+        // Iterators can't be used in a const fn
+        let mut i = 1;
+
+        while i < pulse_widths.len() {
+            if pulse_widths[i] <= pulse_widths[i - 1].saturating_add(1) {
+                return None;
+            }
+
+            i += 1;
+        }
+
+        Some(Self(pulse_widths))
+    }
+
+    /// Pulse widths of GS1, GS3, ..., GS63
+    pub fn pulse_widths(&self) -> [u8; 32] {
+        self.0
     }
 }
 
 /// Horizontal Scroll Direction
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum HScrollDir {
     /// Left to right
     LeftToRight = 0,
@@ -131,7 +389,6 @@ pub enum HScrollDir {
 
 /// Vertical and horizontal scroll dir
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum VHScrollDir {
     /// Vertical and right horizontal
     VerticalRight = 0b01,
@@ -139,31 +396,21 @@ pub enum VHScrollDir {
     VerticalLeft = 0b10,
 }
 
-/// Frame interval
+/// Frame interval between each scroll step
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum NFrames {
-    /// 2 Frames
-    F2 = 0b111,
-    /// 3 Frames
-    F3 = 0b100,
-    /// 4 Frames
-    F4 = 0b101,
-    /// 5 Frames
-    F5 = 0b000,
-    /// 25 Frames
-    F25 = 0b110,
-    /// 64 Frames
-    F64 = 0b001,
-    /// 128 Frames
-    F128 = 0b010,
-    /// 256 Frames
-    F256 = 0b011,
+    /// 6 Frames
+    F6 = 0b00,
+    /// 10 Frames
+    F10 = 0b01,
+    /// 100 Frames
+    F100 = 0b10,
+    /// 200 Frames
+    F200 = 0b11,
 }
 
 /// Vcomh Deselect level
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum VcomhLevel {
     /// 0.44 * Vcc
     V044 = 0b00000,
@@ -179,7 +426,6 @@ pub enum VcomhLevel {
 
 /// Color mode
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum ColorMode {
     /// 256 colors per pixel
     CM256 = 0x00,
@@ -190,7 +436,6 @@ pub enum ColorMode {
 
 /// Address increment mode
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum AddressIncrementMode {
     /// Horizontal address increment
     Horizontal = 0x00,
@@ -198,3 +443,249 @@ pub enum AddressIncrementMode {
     /// Vertical address increment
     Vertical = 0x01,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_encodes(command: Command, expected: &[u8]) {
+        let (data, len) = command.encode();
+
+        assert_eq!(&data[0..len], expected);
+    }
+
+    fn linear_table() -> [u8; 32] {
+        let mut table = [0; 32];
+
+        for (i, width) in table.iter_mut().enumerate() {
+            *width = 1 + 4 * i as u8;
+        }
+
+        table
+    }
+
+    #[test]
+    fn master_current() {
+        assert_encodes(Command::MasterCurrent(0x0F), &[0x87, 0x0F]);
+        assert_encodes(Command::MasterCurrent(0x07), &[0x87, 0x07]);
+        assert_encodes(Command::MasterCurrent(0xFF), &[0x87, 0x0F]);
+    }
+
+    #[test]
+    fn second_precharge_speed() {
+        // Example from the datasheet, changing only the value for colour B
+        assert_encodes(
+            Command::SecondPrechargeSpeed(0x80, 0x75, 0x80),
+            &[0x8A, 0x80, 0x8B, 0x75, 0x8C, 0x80],
+        );
+    }
+
+    #[test]
+    fn gray_scale_table() {
+        let table = GrayScaleTable::new(linear_table()).unwrap();
+        let (data, len) = Command::GrayScaleTable(table).encode();
+
+        assert_eq!(len, 33);
+        assert_eq!(data[0], 0xB8);
+        assert_eq!(&data[1..33], &linear_table());
+    }
+
+    #[test]
+    fn gray_scale_table_validation() {
+        let mut table = linear_table();
+        table[0] = 0;
+        assert!(GrayScaleTable::new(table).is_none(), "GS1 must be > 0");
+
+        let mut table = linear_table();
+        table[31] = 126;
+        assert!(
+            GrayScaleTable::new(table).is_none(),
+            "max pulse width is 125"
+        );
+
+        let mut table = linear_table();
+        table[2] = table[1] + 1;
+        assert!(
+            GrayScaleTable::new(table).is_none(),
+            "each level must be at least 2 greater than the last"
+        );
+
+        let mut table = linear_table();
+        table[1] = table[0] + 2;
+        assert!(GrayScaleTable::new(table).is_some());
+    }
+
+    #[test]
+    fn linear_gray_scale_table() {
+        assert_encodes(Command::LinearGrayScaleTable, &[0xB9]);
+    }
+
+    #[test]
+    fn power_save() {
+        assert_encodes(Command::PowerSave(true), &[0xB0, 0x1A]);
+        assert_encodes(Command::PowerSave(false), &[0xB0, 0x0B]);
+    }
+
+    #[test]
+    fn precharge_period() {
+        // Reset value
+        assert_encodes(Command::PreChargePeriod(0x4, 0x7), &[0xB1, 0x74]);
+
+        // 0 DCLKs is invalid for both phases
+        assert_encodes(Command::PreChargePeriod(0x0, 0x0), &[0xB1, 0x11]);
+        assert_encodes(Command::PreChargePeriod(0x10, 0x10), &[0xB1, 0xFF]);
+    }
+
+    #[test]
+    fn precharge_level() {
+        assert_encodes(Command::PreChargeLevel(0), &[0xBB, 0x00]);
+        // Reset value, 0.5 * Vcc
+        assert_encodes(Command::PreChargeLevel(31), &[0xBB, 0x3E]);
+        assert_encodes(Command::PreChargeLevel(0xFF), &[0xBB, 0x3E]);
+    }
+
+    #[test]
+    fn dim_mode_setting() {
+        assert_encodes(
+            Command::DimModeSetting {
+                contrast_a: 0x40,
+                contrast_b: 0x30,
+                contrast_c: 0x20,
+                precharge: 0x1F,
+            },
+            &[0xAB, 0x00, 0x40, 0x30, 0x20, 0x1F],
+        );
+
+        let (data, len) = Command::DimModeSetting {
+            contrast_a: 0,
+            contrast_b: 0,
+            contrast_c: 0,
+            precharge: 0xFF,
+        }
+        .encode();
+        assert_eq!(data[len - 1], 0x1F);
+    }
+
+    #[test]
+    fn display_on_off() {
+        assert_encodes(Command::DisplayOnDim, &[0xAC]);
+        assert_encodes(Command::DisplayOn(false), &[0xAE]);
+        assert_encodes(Command::DisplayOn(true), &[0xAF]);
+    }
+
+    #[test]
+    fn command_lock() {
+        assert_encodes(Command::CommandLock(true), &[0xFD, 0x16]);
+        assert_encodes(Command::CommandLock(false), &[0xFD, 0x12]);
+    }
+
+    #[test]
+    fn draw_line() {
+        // Green diagonal across the whole display
+        assert_encodes(
+            Command::DrawLine {
+                start: (0, 0),
+                end: (95, 63),
+                color: 0x07E0,
+            },
+            &[0x21, 0x00, 0x00, 0x5F, 0x3F, 0x00, 0x3F, 0x00],
+        );
+
+        // Red and blue are shifted into the top 5 bits of their 6 bit components
+        assert_encodes(
+            Command::DrawLine {
+                start: (10, 20),
+                end: (30, 5),
+                color: 0xF81F,
+            },
+            &[0x21, 10, 20, 30, 5, 0x3E, 0x00, 0x3E],
+        );
+    }
+
+    #[test]
+    fn draw_rect() {
+        assert_encodes(
+            Command::DrawRect {
+                start: (0, 0),
+                end: (95, 7),
+                outline: 0xF800,
+                fill: 0x001F,
+            },
+            &[
+                0x22, 0x00, 0x00, 0x5F, 0x07, 0x3E, 0x00, 0x00, 0x00, 0x00, 0x3E,
+            ],
+        );
+    }
+
+    #[test]
+    fn fill() {
+        assert_encodes(Command::Fill(false, false), &[0x26, 0x00]);
+        assert_encodes(Command::Fill(true, false), &[0x26, 0x01]);
+        assert_encodes(Command::Fill(false, true), &[0x26, 0x10]);
+        assert_encodes(Command::Fill(true, true), &[0x26, 0x11]);
+    }
+
+    #[test]
+    fn copy() {
+        assert_encodes(
+            Command::Copy {
+                start: (0, 0),
+                end: (47, 31),
+                dest: (48, 32),
+            },
+            &[0x23, 0x00, 0x00, 0x2F, 0x1F, 0x30, 0x20],
+        );
+    }
+
+    #[test]
+    fn dim_window() {
+        assert_encodes(
+            Command::DimWindow {
+                start: (48, 0),
+                end: (95, 15),
+            },
+            &[0x24, 0x30, 0x00, 0x5F, 0x0F],
+        );
+    }
+
+    #[test]
+    fn clear_window() {
+        assert_encodes(
+            Command::ClearWindow {
+                start: (0, 0),
+                end: (95, 63),
+            },
+            &[0x25, 0x00, 0x00, 0x5F, 0x3F],
+        );
+    }
+
+    #[test]
+    fn scroll_setup() {
+        assert_encodes(
+            Command::ScrollSetup {
+                horizontal_offset: 1,
+                start_row: 0,
+                rows: 64,
+                vertical_offset: 0,
+                interval: NFrames::F6,
+            },
+            &[0x27, 0x01, 0x00, 0x40, 0x00, 0x00],
+        );
+        assert_encodes(
+            Command::ScrollSetup {
+                horizontal_offset: 0,
+                start_row: 16,
+                rows: 8,
+                vertical_offset: 63,
+                interval: NFrames::F200,
+            },
+            &[0x27, 0x00, 0x10, 0x08, 0x3F, 0x03],
+        );
+    }
+
+    #[test]
+    fn scroll() {
+        assert_encodes(Command::Scroll(false), &[0x2E]);
+        assert_encodes(Command::Scroll(true), &[0x2F]);
+    }
+}
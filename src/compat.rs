@@ -0,0 +1,173 @@
+//! Adapters for embedded-hal 0.2 peripherals
+//!
+//! The driver is written against the embedded-hal 1.0 traits. HALs that only implement
+//! embedded-hal 0.2 can still be used by wrapping their peripherals in the types in this module,
+//! which are available behind the `eh02` feature.
+//!
+//! ```rust
+//! # use ssd1331::test_helpers::eh02::{Spi, Pin, Delay};
+//! use ssd1331::{
+//!     compat::{Eh02Delay, Eh02Pin, Eh02Spi},
+//!     DisplayRotation::Rotate0,
+//!     Ssd1331,
+//! };
+//!
+//! // embedded-hal 0.2 SPI, pins and delay provided by the HAL
+//! let (spi, dc, rst, delay) = (Spi, Pin, Pin, Delay);
+//!
+//! let mut rst = Eh02Pin::new(rst);
+//! let mut delay = Eh02Delay::new(delay);
+//! let mut display = Ssd1331::new(Eh02Spi::new(spi), Eh02Pin::new(dc), Rotate0);
+//!
+//! display.reset(&mut rst, &mut delay).unwrap();
+//! display.init().unwrap();
+//! ```
+
+use core::fmt::Debug;
+use embedded_hal::{delay, digital, spi};
+use embedded_hal_0_2 as eh02;
+
+/// Error returned by the embedded-hal 0.2 adapters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eh02Error<E> {
+    /// Error from the wrapped peripheral
+    Hal(E),
+
+    /// The operation can't be performed by the wrapped peripheral
+    ///
+    /// [`Eh02Spi`] only supports write operations, which is all this driver needs.
+    ///
+    /// [`Eh02Spi`]: ./struct.Eh02Spi.html
+    Unsupported,
+}
+
+impl<E: Debug> spi::Error for Eh02Error<E> {
+    fn kind(&self) -> spi::ErrorKind {
+        spi::ErrorKind::Other
+    }
+}
+
+impl<E: Debug> digital::Error for Eh02Error<E> {
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+/// Wrap an embedded-hal 0.2 SPI bus to use it as an embedded-hal 1.0 [`SpiDevice`]
+///
+/// The display's chip select line is not managed by this adapter, so it must be tied low or
+/// otherwise held active while the display is in use, as with previous versions of this crate.
+/// Only write operations are supported.
+///
+/// [`SpiDevice`]: https://docs.rs/embedded-hal/1.0.0/embedded_hal/spi/trait.SpiDevice.html
+#[derive(Debug, Clone, Copy)]
+pub struct Eh02Spi<SPI>(SPI);
+
+impl<SPI> Eh02Spi<SPI> {
+    /// Wrap an embedded-hal 0.2 SPI bus
+    pub fn new(spi: SPI) -> Self {
+        Self(spi)
+    }
+
+    /// Release the wrapped SPI bus
+    pub fn release(self) -> SPI {
+        self.0
+    }
+}
+
+impl<SPI, E> spi::ErrorType for Eh02Spi<SPI>
+where
+    SPI: eh02::blocking::spi::Write<u8, Error = E>,
+    E: Debug,
+{
+    type Error = Eh02Error<E>;
+}
+
+impl<SPI, E> spi::SpiDevice for Eh02Spi<SPI>
+where
+    SPI: eh02::blocking::spi::Write<u8, Error = E>,
+    E: Debug,
+{
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        operations.iter_mut().try_for_each(|op| match op {
+            spi::Operation::Write(bytes) => self.0.write(bytes).map_err(Eh02Error::Hal),
+            _ => Err(Eh02Error::Unsupported),
+        })
+    }
+}
+
+/// Wrap an embedded-hal 0.2 output pin to use it as an embedded-hal 1.0 [`OutputPin`]
+///
+/// [`OutputPin`]: https://docs.rs/embedded-hal/1.0.0/embedded_hal/digital/trait.OutputPin.html
+#[derive(Debug, Clone, Copy)]
+pub struct Eh02Pin<PIN>(PIN);
+
+impl<PIN> Eh02Pin<PIN> {
+    /// Wrap an embedded-hal 0.2 output pin
+    pub fn new(pin: PIN) -> Self {
+        Self(pin)
+    }
+
+    /// Release the wrapped pin
+    pub fn release(self) -> PIN {
+        self.0
+    }
+}
+
+impl<PIN, E> digital::ErrorType for Eh02Pin<PIN>
+where
+    PIN: eh02::digital::v2::OutputPin<Error = E>,
+    E: Debug,
+{
+    type Error = Eh02Error<E>;
+}
+
+impl<PIN, E> digital::OutputPin for Eh02Pin<PIN>
+where
+    PIN: eh02::digital::v2::OutputPin<Error = E>,
+    E: Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low().map_err(Eh02Error::Hal)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high().map_err(Eh02Error::Hal)
+    }
+}
+
+/// Wrap an embedded-hal 0.2 microsecond delay to use it as an embedded-hal 1.0 [`DelayNs`]
+///
+/// Nanosecond delays are rounded up to the next whole microsecond.
+///
+/// [`DelayNs`]: https://docs.rs/embedded-hal/1.0.0/embedded_hal/delay/trait.DelayNs.html
+#[derive(Debug, Clone, Copy)]
+pub struct Eh02Delay<DELAY>(DELAY);
+
+impl<DELAY> Eh02Delay<DELAY> {
+    /// Wrap an embedded-hal 0.2 delay
+    pub fn new(delay: DELAY) -> Self {
+        Self(delay)
+    }
+
+    /// Release the wrapped delay
+    pub fn release(self) -> DELAY {
+        self.0
+    }
+}
+
+impl<DELAY> delay::DelayNs for Eh02Delay<DELAY>
+where
+    DELAY: eh02::blocking::delay::DelayUs<u32>,
+{
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_us(ns.div_ceil(1000))
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+}
@@ -0,0 +1,247 @@
+//! Tracking of framebuffer areas changed since the last flush
+//!
+//! Coordinates are in the rotated display space, matching the layout of the framebuffer. By
+//! default a single bounding box around all changes is kept. With the `dirty-tiles` feature
+//! enabled, changes are tracked per 8x8 pixel tile instead, and adjacent changed tiles are
+//! combined into as few rectangles as possible when flushing.
+
+/// Top left and bottom right corners of a rectangular area
+pub(crate) type Area = ((u8, u8), (u8, u8));
+
+/// Bounding box of the framebuffer area changed since the last flush
+#[cfg(not(feature = "dirty-tiles"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DirtyArea {
+    /// Changed area, or `None` if nothing has changed
+    area: Option<Area>,
+}
+
+#[cfg(not(feature = "dirty-tiles"))]
+impl DirtyArea {
+    /// Mark the area between the top left corner `start` and bottom right corner `end` as changed
+    pub(crate) fn mark_area(&mut self, start: (u8, u8), end: (u8, u8)) {
+        self.area = Some(match self.area {
+            Some((min, max)) => (
+                (min.0.min(start.0), min.1.min(start.1)),
+                (max.0.max(end.0), max.1.max(end.1)),
+            ),
+            None => (start, end),
+        });
+    }
+
+    /// Return the changed areas, resetting the tracked area to empty
+    pub(crate) fn take(&mut self) -> impl Iterator<Item = Area> {
+        self.area.take().into_iter()
+    }
+
+    /// Forget any changes, e.g. after the whole framebuffer has been sent to the display
+    pub(crate) fn reset(&mut self) {
+        self.area = None;
+    }
+}
+
+/// Width and height of a tile in pixels
+#[cfg(feature = "dirty-tiles")]
+const TILE_SIZE: u8 = 8;
+
+/// Maximum number of tile rows or columns, in any rotation
+#[cfg(feature = "dirty-tiles")]
+const MAX_TILES: usize = crate::DISPLAY_WIDTH as usize / TILE_SIZE as usize;
+
+/// Bitmap of 8x8 pixel tiles changed since the last flush
+#[cfg(feature = "dirty-tiles")]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DirtyArea {
+    /// One bitmask of changed tile columns for each row of tiles
+    tiles: [u16; MAX_TILES],
+}
+
+#[cfg(feature = "dirty-tiles")]
+impl DirtyArea {
+    /// Mark the area between the top left corner `start` and bottom right corner `end` as changed
+    pub(crate) fn mark_area(&mut self, start: (u8, u8), end: (u8, u8)) {
+        let first_col = start.0 / TILE_SIZE;
+        let last_col = end.0 / TILE_SIZE;
+        let mask = (u16::MAX >> (15 - (last_col - first_col))) << first_col;
+
+        self.tiles[usize::from(start.1 / TILE_SIZE)..=usize::from(end.1 / TILE_SIZE)]
+            .iter_mut()
+            .for_each(|row| *row |= mask);
+    }
+
+    /// Return the changed areas, resetting the tracked tiles to empty
+    ///
+    /// Horizontal runs of changed tiles are combined with identical runs in the rows below them
+    /// to reduce the number of areas returned.
+    pub(crate) fn take(&mut self) -> impl Iterator<Item = Area> {
+        let mut tiles = core::mem::take(&mut self.tiles);
+
+        core::iter::from_fn(move || {
+            let first_row = tiles.iter().position(|row| *row != 0)?;
+
+            // Find the first run of changed tiles in the topmost row with changes
+            let row = tiles[first_row];
+            let first_col = row.trailing_zeros();
+            let len = (!(row >> first_col)).trailing_zeros();
+            let mask = (u16::MAX >> (16 - len)) << first_col;
+
+            // Extend the run downwards as long as the rows below contain the same run
+            let rows = tiles[first_row..]
+                .iter()
+                .take_while(|row| *row & mask == mask)
+                .count();
+
+            tiles[first_row..first_row + rows]
+                .iter_mut()
+                .for_each(|row| *row &= !mask);
+
+            let first_col = first_col as u8;
+            let first_row = first_row as u8;
+
+            Some((
+                (first_col * TILE_SIZE, first_row * TILE_SIZE),
+                (
+                    (first_col + len as u8) * TILE_SIZE - 1,
+                    (first_row + rows as u8) * TILE_SIZE - 1,
+                ),
+            ))
+        })
+    }
+
+    /// Forget any changes, e.g. after the whole framebuffer has been sent to the display
+    pub(crate) fn reset(&mut self) {
+        self.tiles = [0; MAX_TILES];
+    }
+}
+
+impl DirtyArea {
+    /// Mark a single pixel as changed
+    pub(crate) fn mark(&mut self, x: u8, y: u8) {
+        self.mark_area((x, y), (x, y));
+    }
+}
+
+#[cfg(all(test, not(feature = "dirty-tiles")))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{vec, vec::Vec};
+
+    fn take(dirty: &mut DirtyArea) -> Vec<Area> {
+        dirty.take().collect()
+    }
+
+    #[test]
+    fn single_pixel() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark(10, 20);
+
+        assert_eq!(take(&mut dirty), vec![((10, 20), (10, 20))]);
+    }
+
+    #[test]
+    fn bounding_box_grows() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark(10, 20);
+        dirty.mark_area((30, 5), (40, 8));
+        dirty.mark(20, 10);
+
+        assert_eq!(take(&mut dirty), vec![((10, 5), (40, 20))]);
+    }
+
+    #[test]
+    fn take_resets() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark_area((0, 0), (95, 63));
+
+        assert_eq!(take(&mut dirty), vec![((0, 0), (95, 63))]);
+        assert_eq!(take(&mut dirty), vec![]);
+
+        dirty.mark(5, 5);
+
+        assert_eq!(take(&mut dirty), vec![((5, 5), (5, 5))]);
+    }
+
+    #[test]
+    fn reset() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark_area((10, 10), (20, 20));
+        dirty.reset();
+
+        assert_eq!(take(&mut dirty), vec![]);
+    }
+}
+
+#[cfg(all(test, feature = "dirty-tiles"))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{vec, vec::Vec};
+
+    fn take(dirty: &mut DirtyArea) -> Vec<Area> {
+        dirty.take().collect()
+    }
+
+    #[test]
+    fn opposite_corners() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark(0, 0);
+        dirty.mark(95, 63);
+
+        assert_eq!(
+            take(&mut dirty),
+            vec![((0, 0), (7, 7)), ((88, 56), (95, 63))]
+        );
+        assert_eq!(take(&mut dirty), vec![]);
+    }
+
+    #[test]
+    fn adjacent_tiles_merge() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark(8, 0);
+        dirty.mark(16, 7);
+        dirty.mark_area((12, 8), (20, 15));
+
+        assert_eq!(take(&mut dirty), vec![((8, 0), (23, 15))]);
+    }
+
+    #[test]
+    fn shorter_run_below_is_not_merged() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark_area((0, 0), (15, 7));
+        dirty.mark_area((0, 8), (7, 15));
+
+        assert_eq!(take(&mut dirty), vec![((0, 0), (15, 7)), ((0, 8), (7, 15))]);
+    }
+
+    #[test]
+    fn rotated_grid() {
+        // Rotated by 90 degrees, the display is 64x96 pixels, or 8x12 tiles
+        let mut dirty = DirtyArea::default();
+        dirty.mark(63, 95);
+        dirty.mark(0, 88);
+
+        assert_eq!(
+            take(&mut dirty),
+            vec![((0, 88), (7, 95)), ((56, 88), (63, 95))]
+        );
+
+        dirty.mark_area((0, 0), (63, 95));
+
+        assert_eq!(take(&mut dirty), vec![((0, 0), (63, 95))]);
+    }
+
+    #[test]
+    fn all_columns() {
+        let mut dirty = DirtyArea::default();
+        dirty.mark_area((0, 8), (95, 15));
+
+        assert_eq!(take(&mut dirty), vec![((0, 8), (95, 15))]);
+
+        dirty.mark_area((0, 0), (95, 63));
+
+        assert_eq!(take(&mut dirty), vec![((0, 0), (95, 63))]);
+    }
+}
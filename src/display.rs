@@ -1,14 +1,26 @@
-use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use display_interface_spi::SPIInterface;
+use hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
 
 use crate::{
-    command::{AddressIncrementMode, ColorMode, Command, VcomhLevel},
+    colordepth::{Color256, Color65k, ColorDepth},
+    command::Command,
     displayrotation::DisplayRotation,
+    doublebuffered::DoubleBuffered,
     error::Error,
+    framebuffer::Framebuffer,
+    gamma::GammaTable,
+    power::{DimConfig, DisplayPower},
+    scroll::ScrollConfig,
     DISPLAY_HEIGHT, DISPLAY_WIDTH,
 };
 
-/// 96px x 64px screen with 16 bits (2 bytes) per pixel
-const BUF_SIZE: usize = 96 * 64 * 2;
+/// Size of the framebuffer in bytes, for a 96px x 64px screen with 16 bits (2 bytes) per pixel
+pub const BUF_SIZE: usize = Color65k::BUF_SIZE;
+
+/// Size of the framebuffer in bytes in 256 colour mode, for a 96px x 64px screen with 8 bits (1
+/// byte) per pixel
+pub const BUF_SIZE_256: usize = Color256::BUF_SIZE;
 
 /// SSD1331 display interface
 ///
@@ -83,33 +95,35 @@ const BUF_SIZE: usize = 96 * 64 * 2;
 /// ```
 ///
 /// [`embedded-graphics`]: https://crates.io/crates/embedded-graphics
-pub struct Ssd1331<SPI, DC> {
-    /// Pixel buffer
-    ///
-    /// The display is 16BPP RGB565, so two `u8`s are used for each pixel value
-    buffer: [u8; BUF_SIZE],
+pub struct Ssd1331<DI, B = [u8; BUF_SIZE], C = Color65k> {
+    /// Framebuffer, dirty area and rotation
+    pub(crate) fb: Framebuffer<B, C>,
 
-    /// Which display rotation to use
-    display_rotation: DisplayRotation,
+    /// Whether solid fills are drawn by the display's hardware rectangle drawing
+    #[cfg(feature = "graphics")]
+    hw_fill: bool,
 
-    /// SPI interface
-    spi: SPI,
+    /// Display interface
+    pub(crate) iface: DI,
 
-    /// Data/Command pin
-    dc: DC,
+    /// Whether a frame handed out by `flush_dma` is still being sent to the display
+    pub(crate) dma_pending: bool,
 }
 
-impl<SPI, DC, CommE, PinE> Ssd1331<SPI, DC>
+impl<SPI, DC> Ssd1331<SPIInterface<SPI, DC>>
 where
-    SPI: hal::blocking::spi::Write<u8, Error = CommE>,
-    DC: OutputPin<Error = PinE>,
+    SPI: SpiDevice,
+    DC: OutputPin,
 {
     /// Create new display instance
     ///
     /// Ensure `display.init()` is called before sending data otherwise nothing will be shown.
     ///
     /// The driver allocates a buffer of 96px * 64px * 16bits = 12,288 bytes. This may be too large
-    /// for some target hardware.
+    /// for some target hardware, in which case [`with_buffer`] can be used to provide storage
+    /// elsewhere.
+    ///
+    /// [`with_buffer`]: #method.with_buffer
     ///
     /// # Examples
     ///
@@ -130,281 +144,1420 @@ where
     /// display.flush().unwrap();
     /// ```
     pub fn new(spi: SPI, dc: DC, display_rotation: DisplayRotation) -> Self {
+        Self::with_buffer(spi, dc, display_rotation, [0; BUF_SIZE])
+    }
+}
+
+impl<SPI, DC> Ssd1331<SPIInterface<SPI, DC>, [u8; BUF_SIZE_256], Color256>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Create new display instance in 256 colour mode
+    ///
+    /// Ensure `display.init()` is called before sending data otherwise nothing will be shown.
+    ///
+    /// Pixels are stored as 8 bit RGB332 values, so the driver allocates a buffer of 96px * 64px *
+    /// 8bits = 6,144 bytes. Flushing the display sends half as much data as in the default 65k
+    /// colour mode. With the `graphics` feature enabled, the display can be drawn to with
+    /// `embedded-graphics` using this crate's [`Rgb332`] colour type.
+    ///
+    /// To provide storage for the framebuffer elsewhere, use [`with_buffer_256_color`].
+    ///
+    /// [`Rgb332`]: ./struct.Rgb332.html
+    /// [`with_buffer_256_color`]: #method.with_buffer_256_color
+    ///
+    /// # Examples
+    ///
+    /// ## Create a 256 colour display instance
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use embedded_graphics::prelude::*;
+    /// use ssd1331::{DisplayRotation::Rotate0, Rgb332, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new_256_color(spi, dc, Rotate0);
+    ///
+    /// display.init().unwrap();
+    ///
+    /// // Raw RGB332 value
+    /// display.set_pixel(10, 20, 0b111_000_00);
+    ///
+    /// // Or embedded-graphics' `Rgb332` if the `graphics` feature is enabled
+    /// Pixel(Point::new(10, 30), Rgb332::GREEN)
+    ///     .draw(&mut display)
+    ///     .unwrap();
+    ///
+    /// display.flush().unwrap();
+    /// ```
+    pub fn new_256_color(spi: SPI, dc: DC, display_rotation: DisplayRotation) -> Self {
+        Self::with_buffer_256_color(spi, dc, display_rotation, [0; BUF_SIZE_256])
+    }
+}
+
+impl<SPI, DC, B> Ssd1331<SPIInterface<SPI, DC>, B>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance using the given storage for the framebuffer
+    ///
+    /// Ensure `display.init()` is called before sending data otherwise nothing will be shown.
+    ///
+    /// This allows the framebuffer to be placed in a `static`, a specific RAM section or a DMA
+    /// capable memory region instead of inside the driver struct. The storage must be at least
+    /// [`BUF_SIZE`] bytes long. Its existing contents are kept, so a zeroed buffer will show a
+    /// blank display when flushed.
+    ///
+    /// [`BUF_SIZE`]: ./constant.BUF_SIZE.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ## Use a borrowed buffer
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331, BUF_SIZE};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// // This could also be a `&'static mut [u8; BUF_SIZE]` to keep it off the stack completely
+    /// let mut buffer = [0u8; BUF_SIZE];
+    ///
+    /// let mut display = Ssd1331::with_buffer(spi, dc, Rotate0, &mut buffer);
+    ///
+    /// // Initialise and clear the display
+    /// display.init().unwrap();
+    /// display.flush().unwrap();
+    /// ```
+    pub fn with_buffer(spi: SPI, dc: DC, display_rotation: DisplayRotation, buffer: B) -> Self {
+        Self::from_interface(SPIInterface::new(spi, dc), display_rotation, buffer)
+    }
+}
+
+impl<SPI, DC, B> Ssd1331<SPIInterface<SPI, DC>, B, Color256>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance in 256 colour mode using the given storage for the framebuffer
+    ///
+    /// This is the 256 colour equivalent of [`with_buffer`]. The storage must be at least
+    /// [`BUF_SIZE_256`] bytes long.
+    ///
+    /// [`with_buffer`]: #method.with_buffer
+    /// [`BUF_SIZE_256`]: ./constant.BUF_SIZE_256.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE_256`] bytes.
+    pub fn with_buffer_256_color(
+        spi: SPI,
+        dc: DC,
+        display_rotation: DisplayRotation,
+        buffer: B,
+    ) -> Self {
+        Self::from_interface_256_color(SPIInterface::new(spi, dc), display_rotation, buffer)
+    }
+}
+
+impl<DI, B> Ssd1331<DI, B>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance using the given display interface and framebuffer storage
+    ///
+    /// Ensure `display.init()` is called before sending data otherwise nothing will be shown.
+    ///
+    /// This allows the display to be driven over any bus implementing [`display-interface`]'s
+    /// `WriteOnlyDataCommand` trait, for example an 8 bit 6800 or 8080 parallel interface. The
+    /// other constructors use [`display-interface-spi`] for 4 wire SPI. The storage must be at
+    /// least [`BUF_SIZE`] bytes long.
+    ///
+    /// [`display-interface`]: https://docs.rs/display-interface
+    /// [`display-interface-spi`]: https://docs.rs/display-interface-spi
+    /// [`BUF_SIZE`]: ./constant.BUF_SIZE.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ## Use an existing display interface
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use display_interface_spi::SPIInterface;
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331, BUF_SIZE};
+    ///
+    /// // Any other `WriteOnlyDataCommand` implementation can be used here
+    /// let interface = SPIInterface::new(Spi, Pin);
+    ///
+    /// let mut display = Ssd1331::from_interface(interface, Rotate0, [0; BUF_SIZE]);
+    ///
+    /// display.init().unwrap();
+    /// display.flush().unwrap();
+    /// ```
+    pub fn from_interface(iface: DI, display_rotation: DisplayRotation, buffer: B) -> Self {
+        Self::from_parts(iface, display_rotation, buffer)
+    }
+}
+
+impl<DI, B> Ssd1331<DI, B, Color256>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+{
+    /// Create new display instance in 256 colour mode using the given display interface and
+    /// framebuffer storage
+    ///
+    /// This is the 256 colour equivalent of [`from_interface`]. The storage must be at least
+    /// [`BUF_SIZE_256`] bytes long.
+    ///
+    /// [`from_interface`]: #method.from_interface
+    /// [`BUF_SIZE_256`]: ./constant.BUF_SIZE_256.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE_256`] bytes.
+    pub fn from_interface_256_color(
+        iface: DI,
+        display_rotation: DisplayRotation,
+        buffer: B,
+    ) -> Self {
+        Self::from_parts(iface, display_rotation, buffer)
+    }
+}
+
+impl<DI, B, C> Ssd1331<DI, B, C>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    /// Create new display instance from its parts, checking the buffer is large enough
+    fn from_parts(iface: DI, display_rotation: DisplayRotation, buffer: B) -> Self {
         Self {
-            spi,
-            dc,
-            display_rotation,
-            buffer: [0; BUF_SIZE],
+            iface,
+            dma_pending: false,
+            fb: Framebuffer::new(display_rotation, buffer),
+            #[cfg(feature = "graphics")]
+            hw_fill: false,
         }
     }
 
-    /// Release SPI and DC resources for reuse in other code
-    pub fn release(self) -> (SPI, DC) {
-        (self.spi, self.dc)
+    /// Release the display interface for reuse in other code
+    ///
+    /// For displays created with an SPI bus and DC pin, call `release()` on the returned interface
+    /// to get them back.
+    pub fn release(self) -> DI {
+        self.iface
     }
 
     /// Clear the display buffer
     ///
     /// `display.flush()` must be called to update the display
     pub fn clear(&mut self) {
-        self.buffer = [0; BUF_SIZE];
+        self.fb.clear();
     }
 
     /// Reset the display
     ///
     /// This method brings the RST pin low for 1ms to reset the module,  waits for another 1ms then
     /// brings RST high
-    pub fn reset<RST, DELAY>(
+    pub fn reset<RST, DELAY, PinE>(
         &mut self,
         rst: &mut RST,
         delay: &mut DELAY,
-    ) -> Result<(), Error<CommE, PinE>>
+    ) -> Result<(), Error<(), PinE>>
     where
         RST: OutputPin<Error = PinE>,
-        DELAY: DelayMs<u8>,
+        DELAY: DelayNs,
     {
-        rst.set_high().map_err(Error::Pin)?;
-        delay.delay_ms(1);
-        rst.set_low().map_err(Error::Pin)?;
-        delay.delay_ms(1);
-        rst.set_high().map_err(Error::Pin)?;
-
-        Ok(())
+        reset(rst, delay)
     }
 
     /// Send the full framebuffer to the display
     ///
     /// This resets the draw area the full size of the display
-    pub fn flush(&mut self) -> Result<(), Error<CommE, PinE>> {
+    pub fn flush(&mut self) -> Result<(), DisplayError> {
         // Ensure the display buffer is at the origin of the display before we send the full frame
         // to prevent accidental offsets
         self.set_draw_area((0, 0), (DISPLAY_WIDTH - 1, DISPLAY_HEIGHT - 1))?;
 
-        // 1 = data, 0 = command
-        self.dc.set_high().map_err(Error::Pin)?;
+        self.iface.send_data(DataFormat::U8(self.fb.bytes()))?;
 
-        self.spi.write(&self.buffer).map_err(Error::Comm)?;
+        self.fb.dirty.reset();
 
         Ok(())
     }
 
-    /// Set the top left and bottom right corners of a bounding box to draw to
-    pub fn set_draw_area(
-        &mut self,
-        start: (u8, u8),
-        end: (u8, u8),
-    ) -> Result<(), Error<CommE, PinE>> {
-        Command::ColumnAddress(start.0, end.0).send(&mut self.spi, &mut self.dc)?;
-        Command::RowAddress(start.1.into(), (end.1).into()).send(&mut self.spi, &mut self.dc)?;
-        Ok(())
-    }
-
-    /// Set the value for an individual pixel.
-    pub fn set_pixel(&mut self, x: u32, y: u32, value: u16) {
-        let idx = match self.display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                if x >= DISPLAY_WIDTH as u32 {
-                    return;
-                }
-                ((y as usize) * DISPLAY_WIDTH as usize) + (x as usize)
-            }
-
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                if y >= DISPLAY_WIDTH as u32 {
-                    return;
-                }
-                ((y as usize) * DISPLAY_HEIGHT as usize) + (x as usize)
-            }
-        } * 2;
+    /// Prepare the display to receive a full frame and hand out the framebuffer so it can be sent
+    /// by a DMA transfer
+    ///
+    /// This sets the draw area to the full display and puts the display interface into data mode,
+    /// then swaps `next` in as the framebuffer and returns the previous framebuffer storage. The
+    /// current frame is copied into `next` first, so drawing can carry on from the same contents
+    /// while the returned storage is streamed to the display. Everything in the returned frame
+    /// counts as sent, so [`flush_dirty`] won't send it again.
+    ///
+    /// Every call copies the whole frame into `next`, which is 12,288 bytes in the default 65k
+    /// colour mode. [`DoubleBuffered::present_dma`] avoids the copy when the next frame is
+    /// redrawn from scratch.
+    ///
+    /// Only the first [`BUF_SIZE`] bytes (or [`BUF_SIZE_256`] bytes in 256 colour mode) of the
+    /// returned storage should be sent. For the transfer to be handed to a DMA channel, the
+    /// storage must usually be a `&'static mut [u8; BUF_SIZE]` placed in DMA capable memory,
+    /// provided with [`with_buffer`], which implements `embedded-dma`'s `ReadBuffer` trait.
+    ///
+    /// The display interface must be left in data mode by an empty data write, which is the case
+    /// for `display-interface-spi` and parallel interfaces. The frame must then be sent by the
+    /// caller with the display's chip select line still active. This isn't possible with the
+    /// constructors that take an [`SpiDevice`], as the driver owns the device and chip select is
+    /// released at the end of every transaction. Instead, use [`from_interface`] with an
+    /// interface that shares the SPI bus with the code starting the DMA transfer, and tie the
+    /// display's chip select low or control it separately, as in the example below.
+    ///
+    /// Drawing into the framebuffer can carry on as normal during the transfer, but every method
+    /// which communicates with the display returns [`DisplayError::BusWriteError`] without sending
+    /// anything until the returned [`FlushToken`] has been passed to [`finish_flush`].
+    ///
+    /// [`flush_dirty`]: #method.flush_dirty
+    /// [`with_buffer`]: #method.with_buffer
+    /// [`from_interface`]: #method.from_interface
+    /// [`finish_flush`]: #method.finish_flush
+    /// [`FlushToken`]: ./struct.FlushToken.html
+    /// [`DoubleBuffered::present_dma`]: ./struct.DoubleBuffered.html#method.present_dma
+    /// [`DisplayError::BusWriteError`]: https://docs.rs/display-interface/0.5.0/display_interface/enum.DisplayError.html#variant.BusWriteError
+    /// [`BUF_SIZE`]: ./constant.BUF_SIZE.html
+    /// [`BUF_SIZE_256`]: ./constant.BUF_SIZE_256.html
+    /// [`SpiDevice`]: https://docs.rs/embedded-hal/1.0.0/embedded_hal/spi/trait.SpiDevice.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `next` is shorter than [`BUF_SIZE`] bytes, or [`BUF_SIZE_256`] bytes in 256
+    /// colour mode.
+    ///
+    /// # Examples
+    ///
+    /// ## Send frames with DMA while drawing the next one
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Bus, Pin};
+    /// use core::cell::RefCell;
+    /// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+    /// use embedded_hal::{digital::OutputPin, spi::SpiBus};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331, BUF_SIZE};
+    ///
+    /// /// Display interface sharing its SPI bus with the code that starts DMA transfers. The
+    /// /// display's chip select line is tied low.
+    /// struct SharedBusInterface<'a, BUS, DC> {
+    ///     bus: &'a RefCell<BUS>,
+    ///     dc: DC,
+    /// }
+    ///
+    /// impl<BUS: SpiBus, DC: OutputPin> SharedBusInterface<'_, BUS, DC> {
+    ///     fn write(&mut self, words: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         match words {
+    ///             DataFormat::U8(bytes) => self
+    ///                 .bus
+    ///                 .borrow_mut()
+    ///                 .write(bytes)
+    ///                 .map_err(|_| DisplayError::BusWriteError),
+    ///             _ => Err(DisplayError::DataFormatNotImplemented),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl<BUS: SpiBus, DC: OutputPin> WriteOnlyDataCommand for SharedBusInterface<'_, BUS, DC> {
+    ///     fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+    ///         self.write(cmd)
+    ///     }
+    ///
+    ///     fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         self.dc.set_high().map_err(|_| DisplayError::DCError)?;
+    ///         self.write(buf)
+    ///     }
+    /// }
+    ///
+    /// // SPI bus and digital pin. These are stub implementations used in examples.
+    /// let bus = RefCell::new(Bus);
+    /// let dc = Pin;
+    ///
+    /// // On a microcontroller these would be `static`s placed in DMA capable memory
+    /// let front: &'static mut [u8; BUF_SIZE] = Box::leak(Box::new([0; BUF_SIZE]));
+    /// let back: &'static mut [u8; BUF_SIZE] = Box::leak(Box::new([0; BUF_SIZE]));
+    ///
+    /// let interface = SharedBusInterface { bus: &bus, dc };
+    /// let mut display = Ssd1331::from_interface(interface, Rotate0, front);
+    /// display.init().unwrap();
+    ///
+    /// display.set_pixel(10, 20, 0xffff);
+    ///
+    /// let (frame, token) = display.flush_dma(back).unwrap();
+    ///
+    /// // Start a DMA transfer of `frame` on the bus here. A blocking write stands in for it.
+    /// bus.borrow_mut().write(&frame[..]).unwrap();
+    ///
+    /// // Keep drawing while the transfer runs
+    /// display.set_pixel(11, 20, 0xffff);
+    ///
+    /// // Nothing can be sent to the display until the transfer has completed
+    /// assert!(display.flush().is_err());
+    ///
+    /// // Once the transfer has completed
+    /// display.finish_flush(token);
+    ///
+    /// // `frame` can now be passed to the next call to `flush_dma`
+    /// let (frame, token) = display.flush_dma(frame).unwrap();
+    /// # display.finish_flush(token);
+    /// ```
+    pub fn flush_dma(&mut self, next: B) -> Result<(B, FlushToken), DisplayError> {
+        self.prepare_frame()?;
 
-        if idx >= self.buffer.len() - 1 {
-            return;
-        }
+        let frame = self.fb.swap_buffer(next);
 
-        // Split 16 bit value into two bytes
-        let low = (value & 0xff) as u8;
-        let high = ((value & 0xff00) >> 8) as u8;
+        self.fb.dirty.reset();
+        self.dma_pending = true;
 
-        self.buffer[idx] = high;
-        self.buffer[idx + 1] = low;
+        Ok((frame, FlushToken::new()))
     }
 
-    /// Initialise display, setting sensible defaults and rotation
-    pub fn init(&mut self) -> Result<(), Error<CommE, PinE>> {
-        let display_rotation = self.display_rotation;
+    /// Mark a DMA transfer started by [`flush_dma`] as complete
+    ///
+    /// This must be called once the transfer of the frame returned by [`flush_dma`] has finished.
+    /// Until then, every method which communicates with the display returns an error.
+    ///
+    /// [`flush_dma`]: #method.flush_dma
+    pub fn finish_flush(&mut self, token: FlushToken) {
+        let FlushToken { .. } = token;
 
-        Command::DisplayOn(false).send(&mut self.spi, &mut self.dc)?;
-        Command::DisplayClockDiv(0xF, 0x0).send(&mut self.spi, &mut self.dc)?;
-        Command::Multiplex(DISPLAY_HEIGHT - 1).send(&mut self.spi, &mut self.dc)?;
-        Command::StartLine(0).send(&mut self.spi, &mut self.dc)?;
-        Command::DisplayOffset(0).send(&mut self.spi, &mut self.dc)?;
+        self.dma_pending = false;
+    }
 
-        self.set_rotation(display_rotation)?;
+    /// Enable double buffering, using `buffer` as the second framebuffer
+    ///
+    /// The second framebuffer is only needed while double buffering is enabled, so this consumes
+    /// the display and returns a [`DoubleBuffered`] display which owns both buffers. See its
+    /// documentation for details.
+    ///
+    /// [`DoubleBuffered`]: ./struct.DoubleBuffered.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is shorter than [`BUF_SIZE`] bytes, or [`BUF_SIZE_256`] bytes in 256
+    /// colour mode.
+    ///
+    /// [`BUF_SIZE`]: ./constant.BUF_SIZE.html
+    /// [`BUF_SIZE_256`]: ./constant.BUF_SIZE_256.html
+    pub fn enable_double_buffering(self, buffer: B) -> DoubleBuffered<DI, B, C> {
+        DoubleBuffered::new(self, buffer)
+    }
 
-        // Values taken from [here](https://github.com/adafruit/Adafruit-SSD1331-OLED-Driver-Library-for-Arduino/blob/master/Adafruit_SSD1331.cpp#L119-L124)
-        Command::Contrast(0x91, 0x50, 0x7D).send(&mut self.spi, &mut self.dc)?;
-        Command::PreChargePeriod(0x1, 0xF).send(&mut self.spi, &mut self.dc)?;
-        Command::VcomhDeselect(VcomhLevel::V071).send(&mut self.spi, &mut self.dc)?;
-        Command::AllOn(false).send(&mut self.spi, &mut self.dc)?;
-        Command::Invert(false).send(&mut self.spi, &mut self.dc)?;
-        Command::DisplayOn(true).send(&mut self.spi, &mut self.dc)?;
+    /// Set the draw area to the full display and put the interface into data mode, ready for a
+    /// full frame to be sent
+    pub(crate) fn prepare_frame(&mut self) -> Result<(), DisplayError> {
+        self.set_draw_area((0, 0), (DISPLAY_WIDTH - 1, DISPLAY_HEIGHT - 1))?;
 
-        Ok(())
+        // Switch the interface to data mode without sending anything
+        self.iface.send_data(DataFormat::U8(&[]))
     }
 
-    /// Get display dimensions, taking into account the current rotation of the display
+    /// Send only the area of the framebuffer changed since the last flush to the display
+    ///
+    /// The driver keeps track of the bounding box of all pixels changed by [`set_pixel`], [`clear`]
+    /// and `embedded-graphics` drawing operations. Only that area is sent, which can be much
+    /// faster than [`flush`] when small parts of the display are updated. Nothing is sent if the
+    /// framebuffer hasn't changed.
+    ///
+    /// With the `dirty-tiles` feature enabled, changes are tracked in 8x8 pixel tiles instead of a
+    /// single bounding box, and each group of adjacent changed tiles is sent separately. This is
+    /// more efficient when changes are spread across the display.
+    ///
+    /// This leaves the draw area set to the last changed area sent.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    /// [`clear`]: #method.clear
+    /// [`flush`]: #method.flush
     ///
     /// # Examples
     ///
-    /// ## No rotation
+    /// ## Update a single pixel
     ///
     /// ```rust
-    /// # use ssd1331::test_helpers::{Spi, Pin};
-    /// use ssd1331::{DisplayRotation, Ssd1331};
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
     ///
     /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
     /// let spi = Spi;
     /// let dc = Pin;
     ///
-    /// let display = Ssd1331::new(spi, dc, DisplayRotation::Rotate0);
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    /// display.flush().unwrap();
     ///
-    /// assert_eq!(display.dimensions(), (96, 64));
+    /// display.set_pixel(10, 20, 0xffff);
+    ///
+    /// // Only sends the one changed pixel
+    /// display.flush_dirty().unwrap();
     /// ```
+    pub fn flush_dirty(&mut self) -> Result<(), DisplayError> {
+        // Check before taking the changed areas so they aren't lost
+        self.idle_iface()?;
+
+        for (start, end) in self.fb.dirty.take() {
+            self.flush_area(start, end)?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a rectangular area of the framebuffer to the display
     ///
-    /// ## 90 degree rotation rotation
+    /// `start` must be the top left and `end` the bottom right corner of the area, both of which
+    /// must lie within the display.
+    fn flush_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
+        let (gram_start, gram_end) = match self.fb.display_rotation.gram_area(start, end) {
+            Some(area) => area,
+            None => return Ok(()),
+        };
+
+        self.set_draw_area(gram_start, gram_end)?;
+
+        // Rotated modes use vertical address increment and transposed GRAM addresses, so in all
+        // rotations each row of the area in the buffer is the next run of pixels in the window
+        for chunk in self.fb.area_chunks(start, end) {
+            self.iface.send_data(DataFormat::U8(chunk))?;
+        }
+
+        Ok(())
+    }
+
+    /// Encode the framebuffer as a 24 bit BMP image
+    ///
+    /// The image has the same dimensions and orientation as the display in its current rotation,
+    /// and shows what the display will look like after the next [`flush`]. Any hardware drawing
+    /// that bypasses the framebuffer, like [`hw_line`], is not included.
+    ///
+    /// This method requires the `std` feature.
+    ///
+    /// [`flush`]: #method.flush
+    /// [`hw_line`]: #method.hw_line
+    ///
+    /// # Examples
+    ///
+    /// ## Save the display contents to a file
     ///
     /// ```rust
-    /// # use ssd1331::test_helpers::{Spi, Pin};
-    /// use ssd1331::{DisplayRotation, Ssd1331};
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use embedded_graphics::{image::GetPixel, pixelcolor::Rgb565, prelude::*};
+    /// use ssd1331::{DisplayRotation::Rotate90, Ssd1331};
+    /// use tinybmp::Bmp;
     ///
     /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
     /// let spi = Spi;
     /// let dc = Pin;
     ///
-    /// let display = Ssd1331::new(spi, dc, DisplayRotation::Rotate90);
+    /// let mut display = Ssd1331::new(spi, dc, Rotate90);
+    /// display.set_pixel(10, 80, 0xf800);
     ///
-    /// assert_eq!(display.dimensions(), (64, 96));
+    /// let bmp_data = display.screenshot();
+    /// # let path = std::env::temp_dir().join("ssd1331-screenshot.bmp");
+    /// std::fs::write(&path, &bmp_data).unwrap();
+    ///
+    /// let bmp = Bmp::<Rgb565>::from_slice(&bmp_data).unwrap();
+    ///
+    /// assert_eq!(bmp.size(), Size::new(64, 96));
+    /// assert_eq!(bmp.pixel(Point::new(10, 80)), Some(Rgb565::RED));
     /// ```
-    pub fn dimensions(&self) -> (u8, u8) {
-        match self.display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                (DISPLAY_WIDTH, DISPLAY_HEIGHT)
-            }
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                (DISPLAY_HEIGHT, DISPLAY_WIDTH)
-            }
-        }
+    #[cfg(feature = "std")]
+    pub fn screenshot(&self) -> std::vec::Vec<u8> {
+        self.fb.to_bmp()
     }
 
-    /// Set the display rotation
-    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error<CommE, PinE>> {
-        self.display_rotation = rot;
-
-        match rot {
-            DisplayRotation::Rotate0 => {
-                Command::RemapAndColorDepth(
-                    false,
-                    false,
-                    ColorMode::CM65k,
-                    AddressIncrementMode::Horizontal,
-                )
-                .send(&mut self.spi, &mut self.dc)?;
-            }
-            DisplayRotation::Rotate90 => {
-                Command::RemapAndColorDepth(
-                    true,
-                    false,
-                    ColorMode::CM65k,
-                    AddressIncrementMode::Vertical,
-                )
-                .send(&mut self.spi, &mut self.dc)?;
-            }
-            DisplayRotation::Rotate180 => {
-                Command::RemapAndColorDepth(
-                    true,
-                    true,
-                    ColorMode::CM65k,
-                    AddressIncrementMode::Horizontal,
-                )
-                .send(&mut self.spi, &mut self.dc)?;
-            }
-            DisplayRotation::Rotate270 => {
-                Command::RemapAndColorDepth(
-                    false,
-                    true,
-                    ColorMode::CM65k,
-                    AddressIncrementMode::Vertical,
-                )
-                .send(&mut self.spi, &mut self.dc)?;
-            }
-        };
-
+    /// Set the top left and bottom right corners of a bounding box to draw to
+    pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
+        Command::ColumnAddress(start.0, end.0).send(self.idle_iface()?)?;
+        Command::RowAddress(start.1, end.1).send(self.idle_iface()?)?;
         Ok(())
     }
 
-    /// Get the current rotation of the display
-    pub fn rotation(&self) -> DisplayRotation {
-        self.display_rotation
-    }
-
-    /// Turn the display on (eg exiting sleep mode)
-    pub fn turn_on(&mut self) -> Result<(), Error<CommE, PinE>> {
-        Command::DisplayOn(true).send(&mut self.spi, &mut self.dc)
+    /// Set the value for an individual pixel.
+    ///
+    /// The value is a raw RGB565 `u16` in the default 65k colour mode, or a raw RGB332 `u8` in 256
+    /// colour mode.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: C::Raw) {
+        self.fb.set_pixel(x, y, value);
     }
 
-    /// Turn the display off (enter sleep mode)
-    pub fn turn_off(&mut self) -> Result<(), Error<CommE, PinE>> {
-        Command::DisplayOn(false).send(&mut self.spi, &mut self.dc)
+    /// Get the value of an individual pixel from the framebuffer
+    ///
+    /// Coordinates are given in the same rotated coordinate space as [`set_pixel`]. The value is a
+    /// raw RGB565 `u16` in the default 65k colour mode, or a raw RGB332 `u8` in 256 colour mode.
+    /// Returns `None` if the pixel lies outside the display.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    ///
+    /// # Examples
+    ///
+    /// ## Invert a cursor pixel
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate90, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate90);
+    ///
+    /// display.set_pixel(10, 80, 0xf800);
+    ///
+    /// if let Some(value) = display.get_pixel(10, 80) {
+    ///     display.set_pixel(10, 80, !value);
+    /// }
+    ///
+    /// assert_eq!(display.get_pixel(10, 80), Some(0x07ff));
+    ///
+    /// // Outside the rotated 64x96 display
+    /// assert_eq!(display.get_pixel(80, 10), None);
+    /// ```
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<C::Raw> {
+        self.fb.get_pixel(x, y)
     }
-}
-
-#[cfg(feature = "graphics")]
-use embedded_graphics_core::{
-    draw_target::DrawTarget,
-    geometry::Size,
-    geometry::{Dimensions, OriginDimensions},
-    pixelcolor::{
-        raw::{RawData, RawU16},
-        Rgb565,
-    },
-    Pixel,
-};
 
-#[cfg(feature = "graphics")]
-impl<SPI, DC> DrawTarget for Ssd1331<SPI, DC>
+    /// Draw a line directly into the display's RAM using the SSD1331's hardware line drawing
+    ///
+    /// Coordinates are given in the same rotated coordinate space as [`set_pixel`]. The line is
+    /// drawn by the display controller itself, so the framebuffer is not modified and the line
+    /// will be overwritten by the next call to [`flush`].
+    ///
+    /// Lines with either end point outside the display are not drawn.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    /// [`flush`]: #method.flush
+    ///
+    /// # Examples
+    ///
+    /// ## Draw a horizontal separator
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// // Draw a white line across the width of the display
+    /// display.hw_line((0, 10), (95, 10), 0xffff).unwrap();
+    /// ```
+    pub fn hw_line(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+        color: u16,
+    ) -> Result<(), DisplayError> {
+        let (start, end) = match (
+            self.fb.display_rotation.gram_address(start),
+            self.fb.display_rotation.gram_address(end),
+        ) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Ok(()),
+        };
+
+        Command::DrawLine { start, end, color }.send(self.idle_iface()?)
+    }
+
+    /// Draw an outlined or filled rectangle directly into the display's RAM using the SSD1331's
+    /// hardware rectangle drawing
+    ///
+    /// `start` and `end` are opposite corners of the rectangle, given in the same rotated
+    /// coordinate space as [`set_pixel`]. The rectangle is outlined with the `outline` colour and,
+    /// if `fill` is `Some`, filled with the given colour. As with [`hw_line`], the framebuffer is
+    /// not modified.
+    ///
+    /// Corners are used instead of an `embedded-graphics` `Rectangle` so that, like the other
+    /// hardware drawing methods, this can be used without the `graphics` feature.
+    ///
+    /// Rectangles with either corner outside the display are not drawn. The display takes a few
+    /// milliseconds to draw large rectangles, so wait before sending anything else to it as
+    /// described in [`set_hardware_fill`].
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    /// [`hw_line`]: #method.hw_line
+    /// [`set_hardware_fill`]: #method.set_hardware_fill
+    ///
+    /// # Examples
+    ///
+    /// ## Draw a solid red status bar
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display.hw_rect((0, 0), (95, 7), 0xf800, Some(0xf800)).unwrap();
+    /// ```
+    pub fn hw_rect(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+        outline: u16,
+        fill: Option<u16>,
+    ) -> Result<(), DisplayError> {
+        let (start, end) = match self.fb.display_rotation.gram_area(start, end) {
+            Some(area) => area,
+            None => return Ok(()),
+        };
+
+        Command::Fill(fill.is_some(), false).send(self.idle_iface()?)?;
+        Command::DrawRect {
+            start,
+            end,
+            outline,
+            fill: fill.unwrap_or(0),
+        }
+        .send(self.idle_iface()?)
+    }
+
+    /// Copy a rectangular region of the display to another location using the SSD1331's hardware
+    /// window copy
+    ///
+    /// `start` and `end` are opposite corners of the source region and `dest` is the top left
+    /// corner of the destination, all given in the same rotated coordinate space as
+    /// [`set_pixel`]. Any part of the destination that falls outside the display is discarded.
+    ///
+    /// The copy is performed in display RAM, so by default the framebuffer is left untouched and
+    /// the next call to [`flush`] will undo it. Pass `true` for `update_buffer` to apply the same
+    /// copy to the framebuffer so that it stays in sync with the display. The destination is then
+    /// marked as changed, so the next call to [`flush_dirty`] also sends any changes to the source
+    /// region that hadn't been sent before the copy.
+    ///
+    /// Nothing is copied if any of the given points lie outside the display.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    /// [`flush`]: #method.flush
+    /// [`flush_dirty`]: #method.flush_dirty
+    ///
+    /// # Examples
+    ///
+    /// ## Scroll the top half of the display up by 8 pixels
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display.hw_copy((0, 8), (95, 31), (0, 0), true).unwrap();
+    /// ```
+    pub fn hw_copy(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+        dest: (u8, u8),
+        update_buffer: bool,
+    ) -> Result<(), DisplayError> {
+        let (start, end) = (
+            (start.0.min(end.0), start.1.min(end.1)),
+            (start.0.max(end.0), start.1.max(end.1)),
+        );
+
+        let (gram_start, gram_end, gram_dest) = match (
+            self.fb.display_rotation.gram_address(start),
+            self.fb.display_rotation.gram_address(end),
+            self.fb.display_rotation.gram_address(dest),
+        ) {
+            (Some(start), Some(end), Some(dest)) => (start, end, dest),
+            _ => return Ok(()),
+        };
+
+        Command::Copy {
+            start: gram_start,
+            end: gram_end,
+            dest: gram_dest,
+        }
+        .send(self.idle_iface()?)?;
+
+        if update_buffer {
+            self.fb.copy(start, end, dest);
+        }
+
+        Ok(())
+    }
+
+    /// Dim a rectangular region of the display using the SSD1331's hardware dim window command
+    ///
+    /// `start` and `end` are opposite corners of the region, given in the same rotated coordinate
+    /// space as [`set_pixel`]. The dimming is applied to display RAM only, so the framebuffer is
+    /// not modified and the next call to [`flush`] will restore the original brightness.
+    ///
+    /// Nothing is dimmed if either corner lies outside the display.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    /// [`flush`]: #method.flush
+    pub fn hw_dim(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
+        if let Some((start, end)) = self.fb.display_rotation.gram_area(start, end) {
+            Command::DimWindow { start, end }.send(self.idle_iface()?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Clear a rectangular region of the display to black using the SSD1331's hardware clear
+    /// window command
+    ///
+    /// `start` and `end` are opposite corners of the region, given in the same rotated coordinate
+    /// space as [`set_pixel`]. The matching region of the framebuffer is also cleared, so there is
+    /// no need to call [`flush`] afterwards.
+    ///
+    /// Nothing is cleared if either corner lies outside the display.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    /// [`flush`]: #method.flush
+    ///
+    /// # Examples
+    ///
+    /// ## Clear a single widget
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display.hw_clear((48, 0), (95, 15)).unwrap();
+    /// ```
+    pub fn hw_clear(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
+        if let Some((gram_start, gram_end)) = self.fb.display_rotation.gram_area(start, end) {
+            Command::ClearWindow {
+                start: gram_start,
+                end: gram_end,
+            }
+            .send(self.idle_iface()?)?;
+
+            self.fb.fill(
+                (start.0.min(end.0), start.1.min(end.1)),
+                (start.0.max(end.0), start.1.max(end.1)),
+                C::Raw::default(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Draw solid fills and clears from `embedded-graphics` using the SSD1331's hardware rectangle
+    /// drawing
+    ///
+    /// When enabled, [`DrawTarget::fill_solid`] and [`DrawTarget::clear`] send a filled
+    /// rectangle command to the display instead of waiting for the next flush, which makes full
+    /// screen clears almost instant. The framebuffer is updated with the same colour but the
+    /// region is not marked as changed, so [`flush_dirty`] won't send it again. If the command
+    /// can't be sent, the fill is drawn into the framebuffer as usual.
+    ///
+    /// Hardware fills show up on the display immediately, even when double buffering is enabled.
+    /// The display takes a few milliseconds to draw a large rectangle and has no busy flag which
+    /// can be read over SPI, so the caller must wait after each hardware fill before anything
+    /// else is sent to the display, including another hardware fill or a flush. Otherwise the
+    /// following writes to display RAM race the rectangle drawing. Adafruit's SSD1331 driver waits
+    /// 3ms after each fill. Because of this, hardware fills are best enabled only around clears
+    /// and other large fills. Hardware drawing is disabled by default.
+    ///
+    /// [`DrawTarget::fill_solid`]: https://docs.rs/embedded-graphics/0.8.1/embedded_graphics/draw_target/trait.DrawTarget.html#method.fill_solid
+    /// [`DrawTarget::clear`]: https://docs.rs/embedded-graphics/0.8.1/embedded_graphics/draw_target/trait.DrawTarget.html#method.clear
+    /// [`flush_dirty`]: #method.flush_dirty
+    ///
+    /// # Examples
+    ///
+    /// ## Instantly clear the screen between pages
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Delay, Pin, Spi};
+    /// use embedded_graphics::{pixelcolor::Rgb565, prelude::*};
+    /// use embedded_hal::delay::DelayNs;
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface, digital pin and delay. These are stub implementations used in
+    /// // examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    /// let mut delay = Delay;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display.set_hardware_fill(true);
+    /// // `DrawTarget::clear` is called explicitly as it's shadowed by `Ssd1331::clear`
+    /// DrawTarget::clear(&mut display, Rgb565::BLACK).unwrap();
+    /// display.set_hardware_fill(false);
+    ///
+    /// // Wait for the display to finish drawing the rectangle
+    /// delay.delay_ms(3);
+    ///
+    /// // Draw the next page, then send only the parts that changed
+    /// display.flush_dirty().unwrap();
+    /// ```
+    #[cfg(feature = "graphics")]
+    pub fn set_hardware_fill(&mut self, enabled: bool) {
+        self.hw_fill = enabled;
+    }
+
+    /// Start continuously scrolling the display in hardware
+    ///
+    /// Any scrolling already in progress is stopped before the new configuration is applied.
+    ///
+    /// # Examples
+    ///
+    /// ## Scroll a ticker along the bottom 16 rows of the display
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, HScrollDir, NFrames, ScrollConfig, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    /// display.flush().unwrap();
+    ///
+    /// let config =
+    ///     ScrollConfig::horizontal(HScrollDir::RightToLeft, 1, 48, 16).with_interval(NFrames::F10);
+    ///
+    /// display.start_scroll(config).unwrap();
+    ///
+    /// // Later, stop scrolling and restore the display contents from the framebuffer
+    /// display.stop_scroll(true).unwrap();
+    /// ```
+    pub fn start_scroll(&mut self, config: ScrollConfig) -> Result<(), DisplayError> {
+        // Scroll parameters must not be changed while scrolling is active
+        Command::Scroll(false).send(self.idle_iface()?)?;
+
+        Command::ScrollSetup {
+            horizontal_offset: config.horizontal_offset,
+            start_row: config.start_row,
+            rows: config.rows,
+            vertical_offset: config.vertical_offset,
+            interval: config.interval,
+        }
+        .send(self.idle_iface()?)?;
+
+        Command::Scroll(true).send(self.idle_iface()?)
+    }
+
+    /// Stop hardware scrolling
+    ///
+    /// The contents of display RAM are undefined once scrolling has stopped. Pass `true` for
+    /// `reflush` to send the framebuffer to the display again so the display and framebuffer agree.
+    pub fn stop_scroll(&mut self, reflush: bool) -> Result<(), DisplayError> {
+        Command::Scroll(false).send(self.idle_iface()?)?;
+
+        if reflush {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Initialise display, setting sensible defaults and rotation
+    pub fn init(&mut self) -> Result<(), DisplayError> {
+        for command in Command::init_sequence(self.fb.display_rotation, C::COLOR_MODE) {
+            command.send(self.idle_iface()?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    ///
+    /// # Examples
+    ///
+    /// ## No rotation
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Spi, Pin};
+    /// use ssd1331::{DisplayRotation, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let display = Ssd1331::new(spi, dc, DisplayRotation::Rotate0);
+    ///
+    /// assert_eq!(display.dimensions(), (96, 64));
+    /// ```
+    ///
+    /// ## 90 degree rotation rotation
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Spi, Pin};
+    /// use ssd1331::{DisplayRotation, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let display = Ssd1331::new(spi, dc, DisplayRotation::Rotate90);
+    ///
+    /// assert_eq!(display.dimensions(), (64, 96));
+    /// ```
+    pub fn dimensions(&self) -> (u8, u8) {
+        self.fb.dimensions()
+    }
+
+    /// Set the display rotation
+    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DisplayError> {
+        rot.remap_command(C::COLOR_MODE).send(self.idle_iface()?)?;
+
+        self.fb.set_rotation(rot);
+
+        Ok(())
+    }
+
+    /// Get the current rotation of the display
+    pub fn rotation(&self) -> DisplayRotation {
+        self.fb.display_rotation
+    }
+
+    /// Turn the display on (eg exiting sleep mode)
+    pub fn turn_on(&mut self) -> Result<(), DisplayError> {
+        Command::DisplayOn(true).send(self.idle_iface()?)
+    }
+
+    /// Turn the display off (enter sleep mode)
+    pub fn turn_off(&mut self) -> Result<(), DisplayError> {
+        Command::DisplayOn(false).send(self.idle_iface()?)
+    }
+
+    /// Switch the display off, on in dim mode or on at normal brightness
+    ///
+    /// Dim mode uses the contrast and pre-charge voltage set by [`set_dim_config`], and keeps the
+    /// display contents visible while using less power.
+    ///
+    /// [`set_dim_config`]: #method.set_dim_config
+    ///
+    /// # Examples
+    ///
+    /// ## Dim an idle screen
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DimConfig, DisplayPower, DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display
+    ///     .set_dim_config(DimConfig::new(0x20, 0x14, 0x24).with_precharge(16))
+    ///     .unwrap();
+    /// display.set_power_mode(DisplayPower::Dim).unwrap();
+    ///
+    /// // Later, when the user interacts with the device again
+    /// display.set_power_mode(DisplayPower::On).unwrap();
+    /// ```
+    pub fn set_power_mode(&mut self, mode: DisplayPower) -> Result<(), DisplayError> {
+        mode.command().send(self.idle_iface()?)
+    }
+
+    /// Set the contrast and pre-charge voltage used when the display is in
+    /// [`DisplayPower::Dim`] mode
+    ///
+    /// The configuration is stored by the display, so it only needs to be sent once. If the
+    /// display is already dimmed, call [`set_power_mode`] again to apply it.
+    ///
+    /// [`DisplayPower::Dim`]: ./enum.DisplayPower.html#variant.Dim
+    /// [`set_power_mode`]: #method.set_power_mode
+    pub fn set_dim_config(&mut self, config: DimConfig) -> Result<(), DisplayError> {
+        config.command().send(self.idle_iface()?)
+    }
+
+    /// Set the gray scale table used to map pixel values to brightness
+    ///
+    /// The display uses [`GammaTable::LINEAR`] after a reset.
+    ///
+    /// [`GammaTable::LINEAR`]: ./struct.GammaTable.html#associatedconstant.LINEAR
+    ///
+    /// # Examples
+    ///
+    /// ## Match colours to an sRGB monitor
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, GammaTable, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display.set_gamma(GammaTable::GAMMA_2_2).unwrap();
+    /// ```
+    pub fn set_gamma(&mut self, table: GammaTable) -> Result<(), DisplayError> {
+        table.command().send(self.idle_iface()?)
+    }
+
+    /// Set the overall brightness of the display from 0 (dimmest) to 15 (brightest)
+    ///
+    /// This scales the current driven through every pixel to `(level + 1) / 16` of the maximum
+    /// set by [`set_contrast`], so it doesn't affect the colour balance. Larger values are clamped
+    /// to 15, which is also the brightness after a reset.
+    ///
+    /// [`set_contrast`]: #method.set_contrast
+    ///
+    /// # Examples
+    ///
+    /// ## Dim the display at night
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// // Reading from an ambient light sensor, from 0 to 255
+    /// let ambient: u8 = 40;
+    ///
+    /// display.set_brightness(ambient / 16).unwrap();
+    /// ```
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), DisplayError> {
+        Command::MasterCurrent(level).send(self.idle_iface()?)
+    }
+
+    /// Set the contrast of the red, green and blue channels
+    ///
+    /// Higher values drive more current through the pixels of that colour. Adjusting the channels
+    /// separately can be used to correct the colour balance of a panel. [`init`] sets the contrast
+    /// to `(0x7D, 0x50, 0x91)`.
+    ///
+    /// [`init`]: #method.init
+    ///
+    /// # Examples
+    ///
+    /// ## Warm up the white point
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display.set_contrast(0x90, 0x50, 0x60).unwrap();
+    /// ```
+    pub fn set_contrast(&mut self, r: u8, g: u8, b: u8) -> Result<(), DisplayError> {
+        Command::Contrast(b, g, r).send(self.idle_iface()?)
+    }
+
+    /// Send a low level command to the display
+    ///
+    /// This allows the panel to be tuned with commands the driver doesn't otherwise expose. The
+    /// framebuffer is not affected. Commands which change how display RAM is addressed, like
+    /// [`Command::RemapAndColorDepth`], can cause the framebuffer and display to disagree until
+    /// the driver's own settings are restored with [`init`] or [`set_rotation`].
+    ///
+    /// [`Command::RemapAndColorDepth`]: ./command/enum.Command.html#variant.RemapAndColorDepth
+    /// [`init`]: #method.init
+    /// [`set_rotation`]: #method.set_rotation
+    ///
+    /// # Examples
+    ///
+    /// ## Start the display from row 8
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{command::Command, DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// display.send_command(Command::StartLine(8)).unwrap();
+    /// ```
+    pub fn send_command(&mut self, command: Command) -> Result<(), DisplayError> {
+        command.send(self.idle_iface()?)
+    }
+
+    /// Send raw bytes to the display in command mode
+    ///
+    /// This is an escape hatch for commands not covered by [`Command`]. The SSD1331 expects
+    /// command parameters to be sent in command mode too, so `bytes` should contain the command
+    /// byte followed by its parameters. The same caveats as [`send_command`] apply.
+    ///
+    /// [`Command`]: ./command/enum.Command.html
+    /// [`send_command`]: #method.send_command
+    ///
+    /// # Examples
+    ///
+    /// ## Select an external Vcc supply
+    ///
+    /// ```rust
+    /// # use ssd1331::test_helpers::{Pin, Spi};
+    /// use ssd1331::{DisplayRotation::Rotate0, Ssd1331};
+    ///
+    /// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+    /// let spi = Spi;
+    /// let dc = Pin;
+    ///
+    /// let mut display = Ssd1331::new(spi, dc, Rotate0);
+    /// display.init().unwrap();
+    ///
+    /// // Set Master Configuration (0xAD)
+    /// display.send_bytes(&[0xAD, 0x8E]).unwrap();
+    /// ```
+    pub fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), DisplayError> {
+        self.idle_iface()?.send_commands(DataFormat::U8(bytes))
+    }
+
+    /// Get the display interface, or an error if a DMA transfer started by `flush_dma` hasn't
+    /// been finished yet
+    fn idle_iface(&mut self) -> Result<&mut DI, DisplayError> {
+        if self.dma_pending {
+            return Err(DisplayError::BusWriteError);
+        }
+
+        Ok(&mut self.iface)
+    }
+}
+
+/// Proof that a DMA transfer of a frame started by [`Ssd1331::flush_dma`] is in progress
+///
+/// Pass this back to [`Ssd1331::finish_flush`] when the transfer is complete.
+///
+/// [`Ssd1331::flush_dma`]: ./struct.Ssd1331.html#method.flush_dma
+/// [`Ssd1331::finish_flush`]: ./struct.Ssd1331.html#method.finish_flush
+// Not `Copy`, so each token can only be used to finish one flush
+#[allow(missing_copy_implementations)]
+#[derive(Debug)]
+#[must_use = "the flush must be completed with `finish_flush` once the transfer is done"]
+pub struct FlushToken {
+    _private: (),
+}
+
+impl FlushToken {
+    /// Create a token for a transfer which has just been started
+    pub(crate) fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+/// Reset the display by bringing the RST pin low for 1ms, waiting for another 1ms then bringing RST
+/// high
+pub(crate) fn reset<RST, DELAY, PinE>(
+    rst: &mut RST,
+    delay: &mut DELAY,
+) -> Result<(), Error<(), PinE>>
 where
-    SPI: hal::blocking::spi::Write<u8>,
-    DC: OutputPin,
+    RST: OutputPin<Error = PinE>,
+    DELAY: DelayNs,
 {
-    type Color = Rgb565;
+    rst.set_high().map_err(Error::Pin)?;
+    delay.delay_ms(1);
+    rst.set_low().map_err(Error::Pin)?;
+    delay.delay_ms(1);
+    rst.set_high().map_err(Error::Pin)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "graphics")]
+use crate::framebuffer::corners;
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Point, Size},
+    image::GetPixel,
+    pixelcolor::raw::{RawData, RawU16},
+    primitives::Rectangle,
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> DrawTarget for Ssd1331<DI, B, C>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    type Color = C::Color;
     type Error = core::convert::Infallible;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        let bb = self.bounding_box();
+        self.fb.draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.fb.fill_contiguous(area, colors)
+    }
 
-        pixels
-            .into_iter()
-            .filter(|Pixel(pos, _color)| bb.contains(*pos))
-            .for_each(|Pixel(pos, color)| {
-                self.set_pixel(pos.x as u32, pos.y as u32, RawU16::from(color).into_inner())
-            });
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        if !self.hw_fill {
+            return self.fb.fill_solid(area, color);
+        }
+
+        let (start, end) = match corners(&area.intersection(&self.bounding_box())) {
+            Some(corners) => corners,
+            None => return Ok(()),
+        };
+
+        let rgb = RawU16::from(color.into()).into_inner();
+
+        match self.hw_rect(start, end, rgb, Some(rgb)) {
+            // The display RAM is now up to date, so keep the framebuffer in sync without marking
+            // the area as changed
+            Ok(()) => self.fb.fill(start, end, C::color_to_raw(color)),
+            Err(_) => return self.fb.fill_solid(area, color),
+        }
 
         Ok(())
     }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
 }
 
 #[cfg(feature = "graphics")]
-impl<SPI, DC> OriginDimensions for Ssd1331<SPI, DC>
+impl<DI, B, C> GetPixel for Ssd1331<DI, B, C>
 where
-    SPI: hal::blocking::spi::Write<u8>,
-    DC: OutputPin,
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    type Color = C::Color;
+
+    fn pixel(&self, p: Point) -> Option<Self::Color> {
+        if p.x < 0 || p.y < 0 {
+            return None;
+        }
+
+        self.get_pixel(p.x as u32, p.y as u32).map(C::raw_to_color)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> OriginDimensions for Ssd1331<DI, B, C>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
 {
     fn size(&self) -> Size {
-        let (w, h) = self.dimensions();
+        self.fb.size()
+    }
+}
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{Pin, Spi};
+    use embedded_graphics_core::pixelcolor::{Rgb565, RgbColor};
+
+    fn fill_red(display: &mut Ssd1331<SPIInterface<Spi, Pin>>) {
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(10, 20), Size::new(4, 3)),
+                Rgb565::RED,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn hardware_fill_updates_buffer_without_marking_it_changed() {
+        let mut display = Ssd1331::new(Spi, Pin, DisplayRotation::Rotate0);
+        display.set_hardware_fill(true);
+
+        fill_red(&mut display);
+
+        assert_eq!(display.get_pixel(10, 20), Some(0xf800));
+        assert_eq!(display.get_pixel(13, 22), Some(0xf800));
+        assert_eq!(display.get_pixel(14, 22), Some(0));
+        assert_eq!(display.get_pixel(13, 23), Some(0));
+        assert_eq!(display.fb.dirty.take().next(), None);
+    }
+
+    #[test]
+    fn software_fill_marks_buffer_changed() {
+        let mut display = Ssd1331::new(Spi, Pin, DisplayRotation::Rotate0);
+
+        fill_red(&mut display);
+
+        assert_eq!(display.get_pixel(10, 20), Some(0xf800));
+        assert!(display.fb.dirty.take().next().is_some());
+    }
+
+    #[test]
+    fn hardware_fill_falls_back_to_buffer_during_dma() {
+        let mut display = Ssd1331::new(Spi, Pin, DisplayRotation::Rotate0);
+        display.set_hardware_fill(true);
+
+        let (_frame, token) = display.flush_dma([0; BUF_SIZE]).unwrap();
+
+        fill_red(&mut display);
+
+        assert_eq!(display.get_pixel(13, 22), Some(0xf800));
+        assert!(display.fb.dirty.take().next().is_some());
 
-        Size::new(w.into(), h.into())
+        display.finish_flush(token);
     }
 }
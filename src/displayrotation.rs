@@ -1,3 +1,8 @@
+use crate::{
+    command::{AddressIncrementMode, ColorMode, Command},
+    DISPLAY_HEIGHT, DISPLAY_WIDTH,
+};
+
 /// Display rotation
 #[derive(Clone, Copy)]
 pub enum DisplayRotation {
@@ -10,3 +15,77 @@ pub enum DisplayRotation {
     /// Rotate 270 degrees clockwise
     Rotate270,
 }
+
+impl DisplayRotation {
+    /// Width and height of the display in this rotation
+    pub(crate) fn dimensions(self) -> (u8, u8) {
+        match self {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                (DISPLAY_WIDTH, DISPLAY_HEIGHT)
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                (DISPLAY_HEIGHT, DISPLAY_WIDTH)
+            }
+        }
+    }
+
+    /// Remap command that sets up the display's RAM addressing and scan direction for this
+    /// rotation, along with the given colour mode
+    pub(crate) fn remap_command(self, color_mode: ColorMode) -> Command {
+        match self {
+            DisplayRotation::Rotate0 => Command::RemapAndColorDepth(
+                false,
+                false,
+                color_mode,
+                AddressIncrementMode::Horizontal,
+            ),
+            DisplayRotation::Rotate90 => {
+                Command::RemapAndColorDepth(true, false, color_mode, AddressIncrementMode::Vertical)
+            }
+            DisplayRotation::Rotate180 => Command::RemapAndColorDepth(
+                true,
+                true,
+                color_mode,
+                AddressIncrementMode::Horizontal,
+            ),
+            DisplayRotation::Rotate270 => {
+                Command::RemapAndColorDepth(false, true, color_mode, AddressIncrementMode::Vertical)
+            }
+        }
+    }
+
+    /// Translate an `(x, y)` coordinate in the rotated display space into a `(column, row)`
+    /// address in display RAM
+    ///
+    /// Returns `None` if the coordinate lies outside the display.
+    pub(crate) fn gram_address(self, (x, y): (u8, u8)) -> Option<(u8, u8)> {
+        let (w, h) = self.dimensions();
+
+        if x >= w || y >= h {
+            return None;
+        }
+
+        match self {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => Some((x, y)),
+            // Rotated modes use vertical address increment, so each buffer row fills a column
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => Some((y, x)),
+        }
+    }
+
+    /// Translate two opposite corners of a rectangle in the rotated display space into the top
+    /// left and bottom right `(column, row)` addresses of the same area in display RAM
+    ///
+    /// Pixel data for the area can be sent row by row in the rotated display space, as the
+    /// address increment mode for each rotation matches the layout of the rotated display.
+    ///
+    /// Returns `None` if either corner lies outside the display.
+    pub(crate) fn gram_area(self, start: (u8, u8), end: (u8, u8)) -> Option<((u8, u8), (u8, u8))> {
+        let start = self.gram_address(start)?;
+        let end = self.gram_address(end)?;
+
+        Some((
+            (start.0.min(end.0), start.1.min(end.1)),
+            (start.0.max(end.0), start.1.max(end.1)),
+        ))
+    }
+}
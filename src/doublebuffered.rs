@@ -0,0 +1,248 @@
+use core::ops::{Deref, DerefMut};
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+use crate::{
+    colordepth::{Color65k, ColorDepth},
+    display::{FlushToken, Ssd1331},
+    framebuffer::Framebuffer,
+    BUF_SIZE,
+};
+
+/// Double buffered SSD1331 display
+///
+/// Created by [`Ssd1331::enable_double_buffering`]. Drawing goes into a back buffer while the
+/// front buffer holds the frame last sent to the display. [`present`] or [`present_dma`] swap the
+/// two buffers and send the completed frame, so a frame is never changed while it is being sent.
+/// This is useful for tear-free animation when frames are sent in the background with DMA.
+///
+/// The buffers are swapped without copying, so after presenting, the back buffer holds the frame
+/// presented before the last one. Each frame should be completely redrawn, for example by clearing
+/// it first.
+///
+/// All other methods of [`Ssd1331`] are available through `Deref`, and act on the back buffer.
+///
+/// [`Ssd1331::enable_double_buffering`]: ./struct.Ssd1331.html#method.enable_double_buffering
+/// [`Ssd1331`]: ./struct.Ssd1331.html
+/// [`present`]: #method.present
+/// [`present_dma`]: #method.present_dma
+///
+/// # Examples
+///
+/// ## Animate a moving square
+///
+/// ```rust
+/// # use ssd1331::test_helpers::{Pin, Spi};
+/// use embedded_graphics::{
+///     pixelcolor::Rgb565,
+///     prelude::*,
+///     primitives::{PrimitiveStyle, Rectangle},
+/// };
+/// use ssd1331::{DisplayRotation::Rotate0, Ssd1331, BUF_SIZE};
+///
+/// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+/// let spi = Spi;
+/// let dc = Pin;
+///
+/// let mut display = Ssd1331::new(spi, dc, Rotate0);
+/// display.init().unwrap();
+///
+/// let mut display = display.enable_double_buffering([0; BUF_SIZE]);
+///
+/// for x in 0..80 {
+///     display.clear(Rgb565::BLACK).unwrap();
+///
+///     Rectangle::new(Point::new(x, 24), Size::new(16, 16))
+///         .into_styled(PrimitiveStyle::with_fill(Rgb565::GREEN))
+///         .draw(&mut display)
+///         .unwrap();
+///
+///     display.present().unwrap();
+/// }
+///
+/// // Go back to a single buffer, getting the second buffer back
+/// let (display, buffer) = display.disable_double_buffering();
+/// ```
+pub struct DoubleBuffered<DI, B = [u8; BUF_SIZE], C = Color65k> {
+    /// Display, whose framebuffer is the back buffer
+    display: Ssd1331<DI, B, C>,
+
+    /// Front buffer holding the last presented frame, or `None` while it is handed out by
+    /// `present_dma`
+    front: Option<B>,
+}
+
+impl<DI, B, C> DoubleBuffered<DI, B, C>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    /// Add a front buffer to a display
+    pub(crate) fn new(display: Ssd1331<DI, B, C>, front: B) -> Self {
+        Framebuffer::<B, C>::check_len(&front);
+
+        Self {
+            display,
+            front: Some(front),
+        }
+    }
+
+    /// Disable double buffering, returning the single buffered display and the second
+    /// framebuffer
+    ///
+    /// The second framebuffer is `None` if it is currently handed out by [`present_dma`].
+    ///
+    /// [`present_dma`]: #method.present_dma
+    pub fn disable_double_buffering(self) -> (Ssd1331<DI, B, C>, Option<B>) {
+        (self.display, self.front)
+    }
+
+    /// Swap the back and front buffers and send the completed frame to the display
+    ///
+    /// Drawing continues into the previous front buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the front buffer is handed out by [`present_dma`] and [`finish_present`] hasn't
+    /// been called yet.
+    ///
+    /// [`present_dma`]: #method.present_dma
+    /// [`finish_present`]: #method.finish_present
+    pub fn present(&mut self) -> Result<(), DisplayError> {
+        let (frame, token) = self.present_dma()?;
+
+        // The interface is already in data mode, so the frame can be sent straight away
+        let result = self
+            .display
+            .iface
+            .send_data(DataFormat::U8(&frame.as_ref()[..C::BUF_SIZE]));
+
+        self.finish_present(token, frame);
+
+        result
+    }
+
+    /// Swap the back and front buffers and hand out the completed frame so it can be sent by a
+    /// DMA transfer
+    ///
+    /// This works like [`Ssd1331::flush_dma`], except the second buffer is managed by the driver
+    /// and the frame isn't copied. The returned storage must be given back to [`finish_present`]
+    /// along with the token once the transfer is complete, after which it becomes the front
+    /// buffer.
+    ///
+    /// [`Ssd1331::flush_dma`]: ./struct.Ssd1331.html#method.flush_dma
+    /// [`finish_present`]: #method.finish_present
+    ///
+    /// # Panics
+    ///
+    /// Panics if the previous frame handed out by `present_dma` hasn't been given back to
+    /// [`finish_present`] yet.
+    pub fn present_dma(&mut self) -> Result<(B, FlushToken), DisplayError> {
+        let back = self.front.take().expect("a frame is still being presented");
+
+        if let Err(e) = self.display.prepare_frame() {
+            self.front = Some(back);
+
+            return Err(e);
+        }
+
+        let frame = self.display.fb.replace_buffer(back);
+
+        self.display.fb.dirty.reset();
+        self.display.dma_pending = true;
+
+        Ok((frame, FlushToken::new()))
+    }
+
+    /// Mark a DMA transfer started by [`present_dma`] as complete, giving back the frame storage
+    ///
+    /// [`present_dma`]: #method.present_dma
+    pub fn finish_present(&mut self, token: FlushToken, frame: B) {
+        self.display.finish_flush(token);
+
+        self.front = Some(frame);
+    }
+}
+
+impl<DI, B, C> Deref for DoubleBuffered<DI, B, C> {
+    type Target = Ssd1331<DI, B, C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.display
+    }
+}
+
+impl<DI, B, C> DerefMut for DoubleBuffered<DI, B, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.display
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Point, Size},
+    image::GetPixel,
+    primitives::Rectangle,
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> DrawTarget for DoubleBuffered<DI, B, C>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    type Color = C::Color;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.display.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        DrawTarget::clear(&mut self.display, color)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> GetPixel for DoubleBuffered<DI, B, C>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    type Color = C::Color;
+
+    fn pixel(&self, p: Point) -> Option<Self::Color> {
+        self.display.pixel(p)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, B, C> OriginDimensions for DoubleBuffered<DI, B, C>
+where
+    DI: WriteOnlyDataCommand,
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
@@ -0,0 +1,594 @@
+use core::{marker::PhantomData, ops::Range};
+
+use crate::{colordepth::ColorDepth, dirty::DirtyArea, displayrotation::DisplayRotation};
+
+/// Framebuffer storage and drawing, shared by the blocking and async drivers
+pub(crate) struct Framebuffer<B, C> {
+    /// Pixel buffer
+    ///
+    /// In the default 65k colour mode the display is 16BPP RGB565, so two `u8`s are used for each
+    /// pixel value
+    buffer: B,
+
+    /// Colour depth
+    color_depth: PhantomData<C>,
+
+    /// Area of the buffer changed since the last flush
+    pub(crate) dirty: DirtyArea,
+
+    /// Which display rotation to use
+    pub(crate) display_rotation: DisplayRotation,
+}
+
+impl<B, C> Framebuffer<B, C>
+where
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    /// Create a new framebuffer, checking the buffer is large enough
+    pub(crate) fn new(display_rotation: DisplayRotation, buffer: B) -> Self {
+        Self::check_len(&buffer);
+
+        Self {
+            buffer,
+            color_depth: PhantomData,
+            dirty: DirtyArea::default(),
+            display_rotation,
+        }
+    }
+
+    /// The bytes of the whole frame, in the order they're sent to the display
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.buffer.as_ref()[..C::BUF_SIZE]
+    }
+
+    /// Replace the storage with `buffer`, copying the current frame into it, and return the old
+    /// storage
+    pub(crate) fn swap_buffer(&mut self, mut buffer: B) -> B {
+        Self::check_len(&buffer);
+
+        buffer.as_mut()[..C::BUF_SIZE].copy_from_slice(self.bytes());
+
+        core::mem::replace(&mut self.buffer, buffer)
+    }
+
+    /// Replace the storage with `buffer` without copying the current frame, and return the old
+    /// storage
+    pub(crate) fn replace_buffer(&mut self, buffer: B) -> B {
+        Self::check_len(&buffer);
+
+        core::mem::replace(&mut self.buffer, buffer)
+    }
+
+    /// Check the buffer is large enough for the colour depth
+    pub(crate) fn check_len(buffer: &B) {
+        assert!(
+            buffer.as_ref().len() >= C::BUF_SIZE,
+            "framebuffer is too small for the colour depth"
+        );
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub(crate) fn dimensions(&self) -> (u8, u8) {
+        self.display_rotation.dimensions()
+    }
+
+    /// Change the rotation, marking the whole display as changed
+    pub(crate) fn set_rotation(&mut self, rot: DisplayRotation) {
+        self.display_rotation = rot;
+
+        // The framebuffer layout depends on the rotation, so the whole display must be redrawn
+        self.dirty.reset();
+        self.mark_all();
+    }
+
+    /// Mark the whole display as changed
+    fn mark_all(&mut self) {
+        let (w, h) = self.dimensions();
+        self.dirty.mark_area((0, 0), (w - 1, h - 1));
+    }
+
+    /// Clear the framebuffer
+    pub(crate) fn clear(&mut self) {
+        self.buffer.as_mut()[..C::BUF_SIZE].fill(0);
+        self.mark_all();
+    }
+
+    /// Set the value for an individual pixel, ignoring pixels outside the display
+    pub(crate) fn set_pixel(&mut self, x: u32, y: u32, value: C::Raw) {
+        let (w, h) = self.dimensions();
+
+        if x >= u32::from(w) || y >= u32::from(h) {
+            return;
+        }
+
+        let (x, y) = (x as u8, y as u8);
+        let pixel = self.row_range(y, x, x);
+
+        C::write_raw(value, &mut self.buffer.as_mut()[pixel]);
+
+        self.dirty.mark(x, y);
+    }
+
+    /// Get the value of an individual pixel, or `None` if it lies outside the display
+    pub(crate) fn get_pixel(&self, x: u32, y: u32) -> Option<C::Raw> {
+        let (w, h) = self.dimensions();
+
+        if x >= u32::from(w) || y >= u32::from(h) {
+            return None;
+        }
+
+        Some(C::read_raw(self.row(y as u8, x as u8, x as u8)))
+    }
+
+    /// Bytes of row `y` from column `start_x` to `end_x` inclusive
+    pub(crate) fn row(&self, y: u8, start_x: u8, end_x: u8) -> &[u8] {
+        &self.buffer.as_ref()[self.row_range(y, start_x, end_x)]
+    }
+
+    /// Bytes of the area between the top left corner `start` and bottom right corner `end`, in as
+    /// few contiguous slices as possible
+    ///
+    /// Full width areas are contiguous in the framebuffer, so they're returned as a single slice.
+    /// Otherwise each row of the area is returned separately.
+    pub(crate) fn area_chunks(
+        &self,
+        start: (u8, u8),
+        end: (u8, u8),
+    ) -> impl Iterator<Item = &[u8]> + '_ {
+        // Full width rows follow each other in the framebuffer, so they can all be sent at once
+        let rows_per_chunk = if start.0 == 0 && end.0 == self.dimensions().0 - 1 {
+            end.1 - start.1 + 1
+        } else {
+            1
+        };
+
+        (start.1..=end.1)
+            .step_by(usize::from(rows_per_chunk))
+            .map(move |y| {
+                let first = self.row_range(y, start.0, end.0);
+                let last = self.row_range(y + rows_per_chunk - 1, start.0, end.0);
+
+                &self.buffer.as_ref()[first.start..last.end]
+            })
+    }
+
+    /// Range of framebuffer bytes containing row `y` from column `start_x` to `end_x` inclusive
+    fn row_range(&self, y: u8, start_x: u8, end_x: u8) -> Range<usize> {
+        let row_start = usize::from(y) * usize::from(self.dimensions().0);
+
+        (row_start + usize::from(start_x)) * C::BYTES_PER_PIXEL
+            ..(row_start + usize::from(end_x) + 1) * C::BYTES_PER_PIXEL
+    }
+
+    /// Fill a region of the framebuffer with a single colour
+    ///
+    /// `start` must be the top left and `end` the bottom right corner of the region, both of which
+    /// must lie within the display.
+    pub(crate) fn fill(&mut self, start: (u8, u8), end: (u8, u8), value: C::Raw) {
+        let mut pixel = [0u8; 2];
+        let pixel = &mut pixel[0..C::BYTES_PER_PIXEL];
+        C::write_raw(value, pixel);
+
+        for y in start.1..=end.1 {
+            let row = self.row_range(y, start.0, end.0);
+
+            self.buffer.as_mut()[row]
+                .chunks_exact_mut(C::BYTES_PER_PIXEL)
+                .for_each(|dest| dest.copy_from_slice(pixel));
+        }
+    }
+
+    /// Copy a region of the framebuffer to another location, clipping the destination to the
+    /// display
+    ///
+    /// `start` must be the top left and `end` the bottom right corner of the source region. The
+    /// destination is marked as changed, as the source may contain changes that haven't been sent
+    /// to the display yet.
+    pub(crate) fn copy(&mut self, start: (u8, u8), end: (u8, u8), dest: (u8, u8)) {
+        let (w, h) = self.dimensions();
+
+        let width = (end.0 - start.0 + 1).min(w - dest.0);
+        let height = (end.1 - start.1 + 1).min(h - dest.1);
+
+        for i in 0..height {
+            // Copy rows in an order that doesn't overwrite source rows before they're read
+            let row = if dest.1 > start.1 { height - 1 - i } else { i };
+
+            let src = self.row_range(start.1 + row, start.0, start.0 + width - 1);
+            let dst = self.row_range(dest.1 + row, dest.0, dest.0 + width - 1);
+
+            self.buffer.as_mut().copy_within(src, dst.start);
+        }
+
+        self.dirty
+            .mark_area(dest, (dest.0 + width - 1, dest.1 + height - 1));
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Point, Size},
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// Top left and bottom right corners of a non-empty rectangle that lies within the display
+#[cfg(feature = "graphics")]
+pub(crate) fn corners(area: &Rectangle) -> Option<((u8, u8), (u8, u8))> {
+    let Point { x: x0, y: y0 } = area.top_left;
+    let Point { x: x1, y: y1 } = area.bottom_right()?;
+
+    Some(((x0 as u8, y0 as u8), (x1 as u8, y1 as u8)))
+}
+
+/// Discard the next `n` items of an iterator
+#[cfg(feature = "graphics")]
+pub(crate) fn skip<I: Iterator>(iter: &mut I, n: usize) {
+    if n > 0 {
+        iter.nth(n - 1);
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<B, C> DrawTarget for Framebuffer<B, C>
+where
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    type Color = C::Color;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| {
+                self.set_pixel(pos.x as u32, pos.y as u32, C::color_to_raw(color))
+            });
+
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable = area.intersection(&self.bounding_box());
+
+        let (start, end) = match corners(&drawable) {
+            Some(corners) => corners,
+            None => return Ok(()),
+        };
+
+        // Number of colours to skip before, inside and after the drawable part of each row
+        let area_width = area.size.width as usize;
+        let skip_left = (drawable.top_left.x - area.top_left.x) as usize;
+        let width = drawable.size.width as usize;
+        let skip_right = area_width - skip_left - width;
+
+        let mut colors = colors.into_iter();
+
+        skip(
+            &mut colors,
+            (drawable.top_left.y - area.top_left.y) as usize * area_width,
+        );
+
+        // The framebuffer is laid out in rotated coordinates, so each row of the area is a
+        // contiguous run of bytes in all rotations
+        for y in start.1..=end.1 {
+            let row = self.row_range(y, start.0, end.0);
+
+            skip(&mut colors, skip_left);
+
+            self.buffer.as_mut()[row]
+                .chunks_exact_mut(C::BYTES_PER_PIXEL)
+                .zip(colors.by_ref().take(width))
+                .for_each(|(dest, color)| C::write_raw(C::color_to_raw(color), dest));
+
+            skip(&mut colors, skip_right);
+        }
+
+        self.dirty.mark_area(start, end);
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let drawable = area.intersection(&self.bounding_box());
+
+        if let Some((start, end)) = corners(&drawable) {
+            self.fill(start, end, C::color_to_raw(color));
+            self.dirty.mark_area(start, end);
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<B, C> OriginDimensions for Framebuffer<B, C>
+where
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    fn size(&self) -> Size {
+        let (w, h) = self.dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::{
+        dirty::{Area, DirtyArea},
+        Color65k,
+        DisplayRotation::{self, *},
+        BUF_SIZE,
+    };
+    #[cfg(feature = "graphics")]
+    use embedded_graphics_core::pixelcolor::{raw::RawU16, Rgb565};
+    use std::{vec, vec::Vec};
+
+    type Buffer = Framebuffer<[u8; BUF_SIZE], Color65k>;
+
+    fn framebuffer(rotation: DisplayRotation) -> Buffer {
+        Framebuffer::new(rotation, [0; BUF_SIZE])
+    }
+
+    /// Framebuffer with every byte set to a different value from its neighbours
+    fn patterned(rotation: DisplayRotation) -> Buffer {
+        let mut fb = framebuffer(rotation);
+
+        fb.buffer
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, byte)| *byte = (i % 251) as u8);
+
+        fb
+    }
+
+    fn take_dirty(fb: &mut Buffer) -> Vec<Area> {
+        fb.dirty.take().collect()
+    }
+
+    /// Changed areas reported after marking the area between `start` and `end`
+    fn marked(start: (u8, u8), end: (u8, u8)) -> Vec<Area> {
+        let mut dirty = DirtyArea::default();
+        dirty.mark_area(start, end);
+
+        dirty.take().collect()
+    }
+
+    fn chunks(fb: &Buffer, start: (u8, u8), end: (u8, u8)) -> Vec<&[u8]> {
+        fb.area_chunks(start, end).collect()
+    }
+
+    #[test]
+    fn full_width_area_is_one_chunk() {
+        let fb = patterned(Rotate0);
+
+        assert_eq!(
+            chunks(&fb, (0, 10), (95, 12)),
+            vec![&fb.bytes()[10 * 96 * 2..13 * 96 * 2]]
+        );
+        assert_eq!(chunks(&fb, (0, 0), (95, 63)), vec![fb.bytes()]);
+    }
+
+    #[test]
+    fn full_width_rotated_area_is_one_chunk() {
+        let fb = patterned(Rotate90);
+
+        assert_eq!(
+            chunks(&fb, (0, 90), (63, 95)),
+            vec![&fb.bytes()[90 * 64 * 2..]]
+        );
+        assert_eq!(chunks(&fb, (0, 0), (63, 95)), vec![fb.bytes()]);
+    }
+
+    #[test]
+    fn partial_width_area_is_one_chunk_per_row() {
+        let fb = patterned(Rotate0);
+        let bytes = fb.bytes();
+
+        assert_eq!(
+            chunks(&fb, (2, 10), (5, 12)),
+            vec![
+                &bytes[(10 * 96 + 2) * 2..(10 * 96 + 6) * 2],
+                &bytes[(11 * 96 + 2) * 2..(11 * 96 + 6) * 2],
+                &bytes[(12 * 96 + 2) * 2..(12 * 96 + 6) * 2],
+            ]
+        );
+        assert_eq!(
+            chunks(&fb, (1, 0), (95, 1)),
+            vec![&bytes[2..96 * 2], &bytes[(96 + 1) * 2..2 * 96 * 2]]
+        );
+    }
+
+    #[test]
+    fn single_pixel_chunk() {
+        let fb = patterned(Rotate270);
+
+        assert_eq!(
+            chunks(&fb, (63, 95), (63, 95)),
+            vec![&fb.bytes()[(95 * 64 + 63) * 2..]]
+        );
+    }
+
+    /// Unique value of each pixel before a copy
+    fn pixel_value(x: u8, y: u8) -> u16 {
+        u16::from(y) << 8 | u16::from(x)
+    }
+
+    /// Copy the area between `start` and `end` to `dest` in a framebuffer where every pixel has a
+    /// different value, then check every pixel and the changed area
+    fn check_copy(rotation: DisplayRotation, start: (u8, u8), end: (u8, u8), dest: (u8, u8)) {
+        let mut fb = framebuffer(rotation);
+        let (w, h) = fb.dimensions();
+
+        for y in 0..h {
+            for x in 0..w {
+                fb.set_pixel(x.into(), y.into(), pixel_value(x, y));
+            }
+        }
+
+        fb.dirty.reset();
+        fb.copy(start, end, dest);
+
+        // Bottom right corner of the destination, clipped to the display
+        let dest_end = (
+            (dest.0 + end.0 - start.0).min(w - 1),
+            (dest.1 + end.1 - start.1).min(h - 1),
+        );
+
+        for y in 0..h {
+            for x in 0..w {
+                let expected =
+                    if (dest.0..=dest_end.0).contains(&x) && (dest.1..=dest_end.1).contains(&y) {
+                        pixel_value(x - dest.0 + start.0, y - dest.1 + start.1)
+                    } else {
+                        pixel_value(x, y)
+                    };
+
+                assert_eq!(
+                    fb.get_pixel(x.into(), y.into()),
+                    Some(expected),
+                    "({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+
+        assert_eq!(take_dirty(&mut fb), marked(dest, dest_end));
+    }
+
+    #[test]
+    fn copy_overlapping_down() {
+        check_copy(Rotate0, (10, 10), (29, 29), (15, 20));
+        check_copy(Rotate90, (0, 0), (63, 49), (0, 1));
+    }
+
+    #[test]
+    fn copy_overlapping_up() {
+        check_copy(Rotate0, (10, 10), (29, 29), (5, 0));
+        check_copy(Rotate90, (0, 1), (63, 50), (0, 0));
+    }
+
+    #[test]
+    fn copy_overlapping_same_rows() {
+        check_copy(Rotate0, (0, 0), (49, 9), (3, 0));
+        check_copy(Rotate0, (3, 0), (52, 9), (0, 0));
+    }
+
+    #[test]
+    fn copy_clipped_to_display() {
+        check_copy(Rotate0, (0, 0), (9, 9), (90, 60));
+        check_copy(Rotate180, (0, 0), (19, 19), (80, 10));
+        check_copy(Rotate270, (0, 0), (19, 19), (10, 90));
+    }
+
+    /// Fill a 4x3 area at `top_left` with colours numbered from 1 along each row, then compare
+    /// the result with the same pixels drawn one at a time
+    #[cfg(feature = "graphics")]
+    fn check_fill_contiguous(rotation: DisplayRotation, top_left: (i32, i32)) {
+        let mut fb = framebuffer(rotation);
+        let mut expected = framebuffer(rotation);
+
+        let area = Rectangle::new(Point::new(top_left.0, top_left.1), Size::new(4, 3));
+        let colors = (1..=12).map(|value| Rgb565::from(RawU16::new(value)));
+
+        fb.fill_contiguous(&area, colors).unwrap();
+
+        for (row, y) in (top_left.1..top_left.1 + 3).enumerate() {
+            for (column, x) in (top_left.0..top_left.0 + 4).enumerate() {
+                if x >= 0 && y >= 0 {
+                    let value = (row * 4 + column + 1) as u16;
+
+                    expected.set_pixel(x as u32, y as u32, value);
+                }
+            }
+        }
+
+        assert_eq!(fb.bytes(), expected.bytes());
+        assert_eq!(take_dirty(&mut fb), take_dirty(&mut expected));
+    }
+
+    #[cfg(feature = "graphics")]
+    const ROTATIONS: [DisplayRotation; 4] = [Rotate0, Rotate90, Rotate180, Rotate270];
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fill_contiguous_clipped_left() {
+        for &rotation in &ROTATIONS {
+            check_fill_contiguous(rotation, (-2, 10));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fill_contiguous_clipped_top() {
+        for &rotation in &ROTATIONS {
+            check_fill_contiguous(rotation, (10, -1));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fill_contiguous_clipped_right() {
+        for &rotation in &ROTATIONS {
+            let (w, _) = rotation.dimensions();
+
+            check_fill_contiguous(rotation, (i32::from(w) - 3, 10));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fill_contiguous_clipped_bottom() {
+        for &rotation in &ROTATIONS {
+            let (_, h) = rotation.dimensions();
+
+            check_fill_contiguous(rotation, (10, i32::from(h) - 1));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fill_contiguous_clipped_corners() {
+        for &rotation in &ROTATIONS {
+            let (w, h) = rotation.dimensions();
+
+            check_fill_contiguous(rotation, (-3, -2));
+            check_fill_contiguous(rotation, (i32::from(w) - 1, i32::from(h) - 2));
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fill_contiguous_outside() {
+        let mut fb = framebuffer(Rotate0);
+
+        let area = Rectangle::new(Point::new(96, 0), Size::new(4, 3));
+        let colors = (1..=12).map(|value| Rgb565::from(RawU16::new(value)));
+
+        fb.fill_contiguous(&area, colors).unwrap();
+
+        assert!(fb.bytes().iter().all(|byte| *byte == 0));
+        assert_eq!(take_dirty(&mut fb), vec![]);
+    }
+}
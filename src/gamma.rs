@@ -0,0 +1,87 @@
+use crate::command::{Command, GrayScaleTable};
+
+/// Gray scale table used to map pixel values to brightness
+///
+/// OLED brightness is roughly proportional to the gray scale pulse width of each pixel, so the
+/// display's built in [`LINEAR`] table makes dark colours look brighter than they do on a typical
+/// sRGB monitor. [`GAMMA_2_2`] approximates an sRGB response instead. Custom tables can be created
+/// with [`GammaTable::new`].
+///
+/// Pass a table to [`Ssd1331::set_gamma`] to use it.
+///
+/// [`LINEAR`]: #associatedconstant.LINEAR
+/// [`GAMMA_2_2`]: #associatedconstant.GAMMA_2_2
+/// [`GammaTable::new`]: #method.new
+/// [`Ssd1331::set_gamma`]: ./struct.Ssd1331.html#method.set_gamma
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GammaTable {
+    /// Table to upload, or `None` to use the display's built in linear table
+    table: Option<GrayScaleTable>,
+}
+
+impl GammaTable {
+    /// The display's built in linear table
+    ///
+    /// Each gray scale level `n` has a pulse width of `2n - 1` DCLKs. This is the datasheet's
+    /// default table, loaded when the display is reset, and is also returned by
+    /// `GammaTable::default()`.
+    pub const LINEAR: Self = Self { table: None };
+
+    /// Gamma 2.2 table, approximating the brightness response of an sRGB display
+    ///
+    /// The display requires each odd gray scale level to be at least 2 DCLKs longer than the last,
+    /// so the darkest levels are slightly brighter than a true gamma 2.2 curve.
+    pub const GAMMA_2_2: Self = match Self::new([
+        1, 3, 5, 7, 9, 11, 13, 15, 17, 19, 21, 23, 25, 27, 29, 31, 33, 35, 39, 44, 49, 54, 60, 66,
+        72, 79, 85, 93, 100, 108, 116, 125,
+    ]) {
+        Some(table) => table,
+        None => panic!("invalid gamma 2.2 table"),
+    };
+
+    /// Create a custom table from the pulse widths, in DCLKs, of the odd gray scale levels GS1,
+    /// GS3, ..., GS63
+    ///
+    /// The display derives the even levels by averaging their neighbours. Returns `None` unless
+    /// the first pulse width is greater than 0, each pulse width is at least 2 greater than the
+    /// one before it and none are greater than 125.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ssd1331::GammaTable;
+    ///
+    /// let mut pulse_widths = [0; 32];
+    ///
+    /// for (i, width) in pulse_widths.iter_mut().enumerate() {
+    ///     *width = 2 + 3 * i as u8;
+    /// }
+    ///
+    /// assert!(GammaTable::new(pulse_widths).is_some());
+    ///
+    /// // Pulse widths must increase by at least 2 each level
+    /// pulse_widths[1] = pulse_widths[0] + 1;
+    ///
+    /// assert!(GammaTable::new(pulse_widths).is_none());
+    /// ```
+    pub const fn new(pulse_widths: [u8; 32]) -> Option<Self> {
+        match GrayScaleTable::new(pulse_widths) {
+            Some(table) => Some(Self { table: Some(table) }),
+            None => None,
+        }
+    }
+
+    /// Command which loads this table into the display
+    pub(crate) fn command(&self) -> Command {
+        match self.table {
+            Some(table) => Command::GrayScaleTable(table),
+            None => Command::LinearGrayScaleTable,
+        }
+    }
+}
+
+impl Default for GammaTable {
+    fn default() -> Self {
+        Self::LINEAR
+    }
+}
@@ -1,13 +1,27 @@
 //! SSD1331 OLED display driver
 //!
-//! This crate is an SPI-based driver for the popular SSD1331 colour OLED display. This display uses
-//! an RGB565 colour space on a canvas of 96x64 pixels and runs over SPI. This driver should work
-//! with any device implementing the [embedded-hal] [`blocking::spi::Write`] trait.
+//! This crate is a driver for the popular SSD1331 colour OLED display. This display uses an RGB565
+//! colour space on a canvas of 96x64 pixels and is usually run over SPI. This driver should work
+//! with any device implementing the [embedded-hal] 1.0 [`SpiDevice`] trait. Devices that only
+//! implement embedded-hal 0.2 can be used through the adapters in the [`compat`] module.
+//!
+//! Communication with the display goes through the [`display-interface`] crate, so other buses
+//! supported by the SSD1331, like its 8 bit 6800 and 8080 parallel interfaces, can be used by
+//! passing any `WriteOnlyDataCommand` implementation to [`Ssd1331::from_interface`]. Displays
+//! strapped for 3 wire SPI, without a D/C pin, can be driven with [`ThreeWireInterface`] or
+//! [`PackedThreeWireInterface`].
 //!
 //! [`embedded-graphics`] is also supported behind the `graphics` feature flag (on by default).
 //!
 //! Note that the driver requires at least 12288 bytes (96 x 64 pixels, 16 bits per pixel) of memory
-//! to store the display's framebuffer.
+//! to store the display's framebuffer. This is stored inside the driver by default, but can be
+//! provided separately using [`Ssd1331::with_buffer`]. If there isn't enough memory for a
+//! framebuffer at all, [`UnbufferedSsd1331`] draws directly to the display instead. The display
+//! can also be run in 256 colour mode with [`Ssd1331::new_256_color`], which halves the size of
+//! the framebuffer.
+//!
+//! Advanced users can tune the panel with the low level commands in the [`command`] module, sent
+//! with [`Ssd1331::send_command`].
 //!
 //! # Examples
 //!
@@ -84,10 +98,47 @@
 //! [`embedded-graphics`] crate. This adds the `.draw()` method to the [`Ssd1331`] struct which
 //! accepts any `embedded-graphics` compatible item.
 //!
+//! ## `dirty-tiles`
+//!
+//! Track changes to the framebuffer in 8x8 pixel tiles instead of a single bounding box, so that
+//! [`Ssd1331::flush_dirty`] only sends the areas that actually changed when updates are spread out
+//! across the display. The tile map uses 24 bytes of memory instead of the 5 bytes used by the
+//! bounding box.
+//!
+//! ## `async`
+//!
+//! Enable [`AsyncSsd1331`], a version of the driver for [`embedded-hal-async`] SPI devices whose
+//! initialisation and flushing methods are `async`. This is useful with async executors like
+//! Embassy, where blocking on a full framebuffer transfer would stall other tasks.
+//!
+//! ## `eh02`
+//!
+//! Enable adapters in the [`compat`] module which allow embedded-hal 0.2 SPI buses, output pins
+//! and delays to be used with this driver.
+//!
+//! ## `std`
+//!
+//! Enable [`Ssd1331::screenshot`], which encodes the framebuffer as a BMP image. This is intended
+//! for host side tests and bug reports, and pulls in the standard library.
+//!
 //! [embedded-hal]: https://docs.rs/embedded-hal
-//! [`blocking::spi::Write`]: https://docs.rs/embedded-hal/0.2.3/embedded_hal/blocking/spi/trait.Write.html
+//! [`SpiDevice`]: https://docs.rs/embedded-hal/1.0.0/embedded_hal/spi/trait.SpiDevice.html
+//! [`compat`]: ./compat/index.html
 //! [`Ssd1331`]: ./struct.Ssd1331.html
+//! [`Ssd1331::flush_dirty`]: ./struct.Ssd1331.html#method.flush_dirty
+//! [`Ssd1331::screenshot`]: ./struct.Ssd1331.html#method.screenshot
+//! [`command`]: ./command/index.html
+//! [`Ssd1331::send_command`]: ./struct.Ssd1331.html#method.send_command
+//! [`Ssd1331::with_buffer`]: ./struct.Ssd1331.html#method.with_buffer
+//! [`UnbufferedSsd1331`]: ./struct.UnbufferedSsd1331.html
+//! [`Ssd1331::new_256_color`]: ./struct.Ssd1331.html#method.new_256_color
 //! [`embedded-graphics`]: https://docs.rs/embedded-graphics
+//! [`display-interface`]: https://docs.rs/display-interface
+//! [`Ssd1331::from_interface`]: ./struct.Ssd1331.html#method.from_interface
+//! [`ThreeWireInterface`]: ./struct.ThreeWireInterface.html
+//! [`PackedThreeWireInterface`]: ./struct.PackedThreeWireInterface.html
+//! [`AsyncSsd1331`]: ./struct.AsyncSsd1331.html
+//! [`embedded-hal-async`]: https://docs.rs/embedded-hal-async
 
 #![no_std]
 // #![deny(missing_debug_implementations)]
@@ -102,15 +153,51 @@
 #![deny(unused_qualifications)]
 
 extern crate embedded_hal as hal;
+#[cfg(feature = "std")]
+extern crate std;
 
 const DISPLAY_WIDTH: u8 = 96;
 const DISPLAY_HEIGHT: u8 = 64;
 
-mod command;
+#[cfg(feature = "async")]
+mod async_display;
+mod colordepth;
+pub mod command;
+#[cfg(feature = "eh02")]
+pub mod compat;
+mod dirty;
 mod display;
 mod displayrotation;
+mod doublebuffered;
 mod error;
+mod framebuffer;
+mod gamma;
+mod power;
+#[cfg(feature = "graphics")]
+mod rgb332;
+#[cfg(feature = "std")]
+mod screenshot;
+mod scroll;
 #[doc(hidden)]
 pub mod test_helpers;
+mod threewire;
+mod unbuffered;
+
+pub use crate::{
+    colordepth::{Color256, Color65k, ColorDepth},
+    command::{HScrollDir, NFrames, VHScrollDir},
+    display::{FlushToken, Ssd1331, BUF_SIZE, BUF_SIZE_256},
+    displayrotation::DisplayRotation,
+    doublebuffered::DoubleBuffered,
+    error::Error,
+    gamma::GammaTable,
+    power::{DimConfig, DisplayPower},
+    scroll::ScrollConfig,
+    threewire::{PackedThreeWireInterface, ThreeWireInterface},
+    unbuffered::UnbufferedSsd1331,
+};
 
-pub use crate::{display::Ssd1331, displayrotation::DisplayRotation, error::Error};
+#[cfg(feature = "async")]
+pub use crate::async_display::AsyncSsd1331;
+#[cfg(feature = "graphics")]
+pub use crate::rgb332::Rgb332;
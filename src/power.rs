@@ -0,0 +1,76 @@
+use crate::command::Command;
+
+/// Display power mode
+///
+/// Pass a mode to [`Ssd1331::set_power_mode`] to change it.
+///
+/// [`Ssd1331::set_power_mode`]: ./struct.Ssd1331.html#method.set_power_mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayPower {
+    /// Display off (sleep mode)
+    Off,
+
+    /// Display on with the reduced contrast and pre-charge voltage set by [`DimConfig`]
+    ///
+    /// [`DimConfig`]: ./struct.DimConfig.html
+    Dim,
+
+    /// Display on at normal brightness
+    On,
+}
+
+impl DisplayPower {
+    /// Command which switches the display to this mode
+    pub(crate) fn command(self) -> Command {
+        match self {
+            DisplayPower::Off => Command::DisplayOn(false),
+            DisplayPower::Dim => Command::DisplayOnDim,
+            DisplayPower::On => Command::DisplayOn(true),
+        }
+    }
+}
+
+/// Contrast and pre-charge voltage used in [`DisplayPower::Dim`] mode
+///
+/// Pass a configuration to [`Ssd1331::set_dim_config`] to send it to the display.
+///
+/// [`DisplayPower::Dim`]: ./enum.DisplayPower.html#variant.Dim
+/// [`Ssd1331::set_dim_config`]: ./struct.Ssd1331.html#method.set_dim_config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimConfig {
+    pub(crate) contrast: (u8, u8, u8),
+    pub(crate) precharge: u8,
+}
+
+impl DimConfig {
+    /// Dim mode with the given contrast of the red, green and blue channels
+    ///
+    /// The pre-charge voltage level defaults to 31, the same as normal mode after a reset.
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self {
+            contrast: (r, g, b),
+            precharge: 31,
+        }
+    }
+
+    /// Set the pre-charge voltage level from 0 (0.1 * Vcc) to 31 (0.5 * Vcc). Larger values are
+    /// clamped to 31.
+    pub fn with_precharge(self, level: u8) -> Self {
+        Self {
+            precharge: level.min(31),
+            ..self
+        }
+    }
+
+    /// Command which sends this configuration to the display
+    pub(crate) fn command(&self) -> Command {
+        let (r, g, b) = self.contrast;
+
+        Command::DimModeSetting {
+            contrast_a: b,
+            contrast_b: g,
+            contrast_c: r,
+            precharge: self.precharge,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! 8 bit RGB332 colour type used in 256 colour mode
+//!
+//! `embedded-graphics-core` 0.4 (used by `embedded-graphics` 0.8) only provides 8 bit colour types
+//! for grayscale and binary colours, so an 8 bit RGB colour type is defined here for use with
+//! [`Color256`].
+//!
+//! [`Color256`]: ../struct.Color256.html
+
+use embedded_graphics_core::pixelcolor::{
+    raw::{RawData, RawU8},
+    PixelColor, Rgb565, Rgb888, RgbColor,
+};
+
+/// RGB332 color
+///
+/// Red and green channels are stored with 3 bits each and the blue channel with 2 bits, packed
+/// into a single byte as `RRRGGGBB`. This matches the pixel format used by the SSD1331 in 256
+/// colour mode.
+///
+/// Use the methods provided by the [`RgbColor`] trait to access individual color channels and
+/// predefined color constants. Other RGB colours can be converted to `Rgb332` with `From`/`Into`.
+///
+/// [`RgbColor`]: https://docs.rs/embedded-graphics/0.8.1/embedded_graphics/pixelcolor/trait.RgbColor.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct Rgb332(u8);
+
+impl Rgb332 {
+    /// Create a new colour from red (0-7), green (0-7) and blue (0-3) channel values
+    ///
+    /// Channel values outside these ranges are truncated.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self((r & 0x07) << 5 | (g & 0x07) << 2 | (b & 0x03))
+    }
+}
+
+impl PixelColor for Rgb332 {
+    type Raw = RawU8;
+}
+
+impl RgbColor for Rgb332 {
+    fn r(&self) -> u8 {
+        self.0 >> 5
+    }
+
+    fn g(&self) -> u8 {
+        (self.0 >> 2) & 0x07
+    }
+
+    fn b(&self) -> u8 {
+        self.0 & 0x03
+    }
+
+    const MAX_R: u8 = 0x07;
+    const MAX_G: u8 = 0x07;
+    const MAX_B: u8 = 0x03;
+
+    const BLACK: Self = Self::new(0, 0, 0);
+    const RED: Self = Self::new(Self::MAX_R, 0, 0);
+    const GREEN: Self = Self::new(0, Self::MAX_G, 0);
+    const BLUE: Self = Self::new(0, 0, Self::MAX_B);
+    const YELLOW: Self = Self::new(Self::MAX_R, Self::MAX_G, 0);
+    const MAGENTA: Self = Self::new(Self::MAX_R, 0, Self::MAX_B);
+    const CYAN: Self = Self::new(0, Self::MAX_G, Self::MAX_B);
+    const WHITE: Self = Self::new(Self::MAX_R, Self::MAX_G, Self::MAX_B);
+}
+
+impl From<RawU8> for Rgb332 {
+    fn from(raw: RawU8) -> Self {
+        Self(raw.into_inner())
+    }
+}
+
+impl From<Rgb332> for RawU8 {
+    fn from(color: Rgb332) -> Self {
+        RawU8::new(color.0)
+    }
+}
+
+/// Convert a colour channel value from one bit depth to another, rounding to the nearest value
+const fn convert_channel(value: u8, from_max: u8, to_max: u8) -> u8 {
+    ((value as u16 * to_max as u16 + from_max as u16 / 2) / from_max as u16) as u8
+}
+
+/// Implement conversions between `Rgb332` and another RGB colour type
+macro_rules! impl_rgb_conversion {
+    ($($other:ident),+) => {
+        $(
+            impl From<$other> for Rgb332 {
+                fn from(other: $other) -> Self {
+                    Self::new(
+                        convert_channel(other.r(), $other::MAX_R, Self::MAX_R),
+                        convert_channel(other.g(), $other::MAX_G, Self::MAX_G),
+                        convert_channel(other.b(), $other::MAX_B, Self::MAX_B),
+                    )
+                }
+            }
+
+            impl From<Rgb332> for $other {
+                fn from(color: Rgb332) -> Self {
+                    Self::new(
+                        convert_channel(color.r(), Rgb332::MAX_R, Self::MAX_R),
+                        convert_channel(color.g(), Rgb332::MAX_G, Self::MAX_G),
+                        convert_channel(color.b(), Rgb332::MAX_B, Self::MAX_B),
+                    )
+                }
+            }
+        )+
+    };
+}
+
+impl_rgb_conversion!(Rgb565, Rgb888);
@@ -0,0 +1,64 @@
+//! Framebuffer export to BMP images, available behind the `std` feature
+
+use std::vec::Vec;
+
+use crate::{colordepth::ColorDepth, framebuffer::Framebuffer};
+
+/// Size of the BMP file header and `BITMAPINFOHEADER` in bytes
+const HEADER_LEN: usize = 14 + 40;
+
+impl<B, C> Framebuffer<B, C>
+where
+    B: AsMut<[u8]> + AsRef<[u8]>,
+    C: ColorDepth,
+{
+    /// Encode the framebuffer as an uncompressed 24 bit BMP image
+    ///
+    /// The image has the rotated dimensions of the display, so it matches what is shown on the
+    /// panel after a flush.
+    pub(crate) fn to_bmp(&self) -> Vec<u8> {
+        let (w, h) = self.dimensions();
+
+        // Rows are padded to a multiple of 4 bytes
+        let row_len = (usize::from(w) * 3 + 3) & !3;
+        let image_len = row_len * usize::from(h);
+        let file_len = HEADER_LEN + image_len;
+
+        let mut bmp = Vec::with_capacity(file_len);
+
+        // File header
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&(file_len as u32).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]);
+        bmp.extend_from_slice(&(HEADER_LEN as u32).to_le_bytes());
+
+        // BITMAPINFOHEADER
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&i32::from(w).to_le_bytes());
+        bmp.extend_from_slice(&i32::from(h).to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&24u16.to_le_bytes());
+        // No compression
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&(image_len as u32).to_le_bytes());
+        // 72 DPI in both directions, no palette
+        bmp.extend_from_slice(&2835u32.to_le_bytes());
+        bmp.extend_from_slice(&2835u32.to_le_bytes());
+        bmp.extend_from_slice(&[0; 8]);
+
+        // Rows are stored bottom to top, with each pixel in BGR order
+        for y in (0..h).rev() {
+            let row_start = bmp.len();
+
+            for pixel in self.row(y, 0, w - 1).chunks_exact(C::BYTES_PER_PIXEL) {
+                let [r, g, b] = C::raw_to_rgb888(C::read_raw(pixel));
+
+                bmp.extend_from_slice(&[b, g, r]);
+            }
+
+            bmp.resize(row_start + row_len, 0);
+        }
+
+        bmp
+    }
+}
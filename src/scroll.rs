@@ -0,0 +1,86 @@
+use crate::{
+    command::{HScrollDir, NFrames, VHScrollDir},
+    DISPLAY_HEIGHT, DISPLAY_WIDTH,
+};
+
+/// Continuous hardware scrolling configuration
+///
+/// Scrolling is performed by the display itself in terms of the physical panel, so directions are
+/// relative to the unrotated display regardless of the current [`DisplayRotation`].
+///
+/// Pass a configuration to [`Ssd1331::start_scroll`] to begin scrolling.
+///
+/// [`DisplayRotation`]: ./enum.DisplayRotation.html
+/// [`Ssd1331::start_scroll`]: ./struct.Ssd1331.html#method.start_scroll
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollConfig {
+    pub(crate) horizontal_offset: u8,
+    pub(crate) start_row: u8,
+    pub(crate) rows: u8,
+    pub(crate) vertical_offset: u8,
+    pub(crate) interval: NFrames,
+}
+
+impl ScrollConfig {
+    /// Scroll `rows` rows starting at `start_row` horizontally by `columns` columns each step
+    ///
+    /// Rows outside the display are ignored.
+    pub fn horizontal(dir: HScrollDir, columns: u8, start_row: u8, rows: u8) -> Self {
+        let start_row = start_row.min(DISPLAY_HEIGHT - 1);
+
+        Self {
+            horizontal_offset: horizontal_offset(dir, columns),
+            start_row,
+            rows: rows.min(DISPLAY_HEIGHT - start_row),
+            vertical_offset: 0,
+            interval: NFrames::F6,
+        }
+    }
+
+    /// Scroll the entire display vertically by `rows` rows each step
+    pub fn vertical(rows: u8) -> Self {
+        Self {
+            horizontal_offset: 0,
+            start_row: 0,
+            rows: 0,
+            vertical_offset: rows % DISPLAY_HEIGHT,
+            interval: NFrames::F6,
+        }
+    }
+
+    /// Scroll the entire display diagonally by `columns` columns and `rows` rows each step
+    pub fn diagonal(dir: VHScrollDir, columns: u8, rows: u8) -> Self {
+        let dir = match dir {
+            VHScrollDir::VerticalRight => HScrollDir::LeftToRight,
+            VHScrollDir::VerticalLeft => HScrollDir::RightToLeft,
+        };
+
+        Self {
+            horizontal_offset: horizontal_offset(dir, columns),
+            start_row: 0,
+            rows: DISPLAY_HEIGHT,
+            vertical_offset: rows % DISPLAY_HEIGHT,
+            interval: NFrames::F6,
+        }
+    }
+
+    /// Set the number of frames between each scroll step. Defaults to [`NFrames::F6`].
+    ///
+    /// [`NFrames::F6`]: ./enum.NFrames.html#variant.F6
+    pub fn with_interval(self, interval: NFrames) -> Self {
+        Self { interval, ..self }
+    }
+}
+
+/// Convert a number of columns and direction into the horizontal offset expected by the display
+///
+/// The display always scrolls in the same direction, so scrolling the other way is achieved by
+/// wrapping around the width of the display.
+fn horizontal_offset(dir: HScrollDir, columns: u8) -> u8 {
+    let columns = columns % DISPLAY_WIDTH;
+
+    match dir {
+        HScrollDir::LeftToRight => columns,
+        HScrollDir::RightToLeft => (DISPLAY_WIDTH - columns) % DISPLAY_WIDTH,
+    }
+}
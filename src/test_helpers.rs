@@ -1,27 +1,66 @@
 //! Helpers for use in examples and tests
 
 use embedded_hal::{
-    blocking::spi::{self, Transfer},
-    digital::v2::OutputPin,
+    delay::DelayNs,
+    digital::{self, OutputPin},
+    spi::{self, Operation, SpiBus, SpiDevice},
 };
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct Spi;
 
-impl spi::Write<u8> for Spi {
-    type Error = ();
+impl spi::ErrorType for Spi {
+    type Error = core::convert::Infallible;
+}
 
-    fn write(&mut self, _buf: &[u8]) -> Result<(), ()> {
+impl SpiDevice for Spi {
+    fn transaction(&mut self, _operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
         Ok(())
     }
 }
 
-impl Transfer<u8> for Spi {
-    type Error = ();
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct Bus;
+
+impl spi::ErrorType for Bus {
+    type Error = core::convert::Infallible;
+}
+
+impl SpiBus for Bus {
+    fn read(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, _words: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn transfer(&mut self, _read: &mut [u8], _write: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
 
-    fn transfer<'a>(&mut self, buf: &'a mut [u8]) -> Result<&'a [u8], ()> {
-        Ok(buf)
+    fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct Spi9;
+
+impl spi::ErrorType for Spi9 {
+    type Error = core::convert::Infallible;
+}
+
+impl SpiDevice<u16> for Spi9 {
+    fn transaction(&mut self, _operations: &mut [Operation<'_, u16>]) -> Result<(), Self::Error> {
+        Ok(())
     }
 }
 
@@ -29,14 +68,99 @@ impl Transfer<u8> for Spi {
 #[derive(Debug, Clone, Copy)]
 pub struct Pin;
 
+impl digital::ErrorType for Pin {
+    type Error = core::convert::Infallible;
+}
+
 impl OutputPin for Pin {
-    type Error = ();
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 
-    fn set_high(&mut self) -> Result<(), ()> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
         Ok(())
     }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct Delay;
 
-    fn set_low(&mut self) -> Result<(), ()> {
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncSpi;
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice for AsyncSpi {
+    async fn transaction(
+        &mut self,
+        _operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
         Ok(())
     }
 }
+
+#[cfg(feature = "async")]
+impl spi::ErrorType for AsyncSpi {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "async")]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncDelay;
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for AsyncDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Stub embedded-hal 0.2 implementations for the `eh02` compatibility adapters
+#[cfg(feature = "eh02")]
+pub mod eh02 {
+    use embedded_hal_0_2::{
+        blocking::{delay::DelayUs, spi},
+        digital::v2::OutputPin,
+    };
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, Copy)]
+    pub struct Spi;
+
+    impl spi::Write<u8> for Spi {
+        type Error = ();
+
+        fn write(&mut self, _buf: &[u8]) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, Copy)]
+    pub struct Pin;
+
+    impl OutputPin for Pin {
+        type Error = ();
+
+        fn set_high(&mut self) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn set_low(&mut self) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, Copy)]
+    pub struct Delay;
+
+    impl DelayUs<u32> for Delay {
+        fn delay_us(&mut self, _us: u32) {}
+    }
+}
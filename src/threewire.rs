@@ -0,0 +1,339 @@
+//! 3 wire SPI display interfaces
+//!
+//! When the SSD1331 is strapped for 3 wire SPI there is no D/C pin. Instead, each byte is sent as
+//! a 9 bit word whose first bit is the D/C flag: `0` for a command and `1` for data.
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::spi::SpiDevice;
+
+/// Number of 9 bit words sent to the display in each SPI write
+///
+/// This is a multiple of 8 so that packed writes never need padding except at the end of a
+/// command or data block.
+const CHUNK_WORDS: usize = 32;
+
+/// Call `f` for each byte of `words`, in the order they should be sent to the display
+fn for_each_byte(
+    words: DataFormat<'_>,
+    mut f: impl FnMut(u8) -> Result<(), DisplayError>,
+) -> Result<(), DisplayError> {
+    match words {
+        DataFormat::U8(slice) => slice.iter().try_for_each(|b| f(*b)),
+        DataFormat::U8Iter(iter) => {
+            for b in iter {
+                f(b)?;
+            }
+
+            Ok(())
+        }
+        DataFormat::U16(slice) => slice
+            .iter()
+            .try_for_each(|w| w.to_ne_bytes().iter().try_for_each(|b| f(*b))),
+        DataFormat::U16BE(slice) => slice
+            .iter()
+            .try_for_each(|w| w.to_be_bytes().iter().try_for_each(|b| f(*b))),
+        DataFormat::U16LE(slice) => slice
+            .iter()
+            .try_for_each(|w| w.to_le_bytes().iter().try_for_each(|b| f(*b))),
+        DataFormat::U16BEIter(iter) => {
+            for w in iter {
+                w.to_be_bytes().iter().try_for_each(|b| f(*b))?;
+            }
+
+            Ok(())
+        }
+        DataFormat::U16LEIter(iter) => {
+            for w in iter {
+                w.to_le_bytes().iter().try_for_each(|b| f(*b))?;
+            }
+
+            Ok(())
+        }
+        _ => Err(DisplayError::DataFormatNotImplemented),
+    }
+}
+
+/// 3 wire SPI interface for SPI peripherals which support 9 bit frames
+///
+/// Each byte is sent as a single `u16` SPI word with the D/C flag in bit 8. The SPI peripheral
+/// must be configured by the HAL for 9 bit frames, MSB first. For SPI peripherals which only
+/// support 8 bit frames, use [`PackedThreeWireInterface`] instead.
+///
+/// [`PackedThreeWireInterface`]: ./struct.PackedThreeWireInterface.html
+///
+/// # Examples
+///
+/// ```rust
+/// # use ssd1331::test_helpers::Spi9;
+/// use ssd1331::{DisplayRotation::Rotate0, Ssd1331, ThreeWireInterface, BUF_SIZE};
+///
+/// // SPI device configured for 9 bit frames. This is a stub implementation used in examples.
+/// let spi = Spi9;
+///
+/// let interface = ThreeWireInterface::new(spi);
+/// let mut display = Ssd1331::from_interface(interface, Rotate0, [0; BUF_SIZE]);
+///
+/// display.init().unwrap();
+/// display.flush().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ThreeWireInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> ThreeWireInterface<SPI>
+where
+    SPI: SpiDevice<u16>,
+{
+    /// Create a new 3 wire interface from an SPI device configured for 9 bit frames
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Release the SPI device for reuse in other code
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    /// Send bytes with the given D/C flag
+    fn send(&mut self, dc: bool, words: DataFormat<'_>) -> Result<(), DisplayError> {
+        let flag = u16::from(dc) << 8;
+        let mut chunk = [0u16; CHUNK_WORDS];
+        let mut len = 0;
+
+        for_each_byte(words, |byte| {
+            chunk[len] = flag | u16::from(byte);
+            len += 1;
+
+            if len == CHUNK_WORDS {
+                len = 0;
+                self.spi
+                    .write(&chunk)
+                    .map_err(|_| DisplayError::BusWriteError)?;
+            }
+
+            Ok(())
+        })?;
+
+        if len > 0 {
+            self.spi
+                .write(&chunk[0..len])
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<SPI> WriteOnlyDataCommand for ThreeWireInterface<SPI>
+where
+    SPI: SpiDevice<u16>,
+{
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(false, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(true, buf)
+    }
+}
+
+/// 3 wire SPI interface for SPI peripherals which only support 8 bit frames
+///
+/// The 9 bit words are packed into a continuous stream of bits, so every 8 words are sent as 9
+/// bytes. When the number of words sent in one write isn't a multiple of 8, the last byte is
+/// padded with zero bits. The padding is shorter than a full word, so it is discarded by the
+/// display when the chip select line is released at the end of the write.
+///
+/// Because of this, the SPI device must release chip select after each write, as is required of
+/// every [`SpiDevice`] implementation.
+///
+/// [`SpiDevice`]: https://docs.rs/embedded-hal/1.0.0/embedded_hal/spi/trait.SpiDevice.html
+///
+/// # Examples
+///
+/// ```rust
+/// # use ssd1331::test_helpers::Spi;
+/// use ssd1331::{DisplayRotation::Rotate0, PackedThreeWireInterface, Ssd1331, BUF_SIZE};
+///
+/// // Set up SPI interface. This is a stub implementation used in examples.
+/// let spi = Spi;
+///
+/// let interface = PackedThreeWireInterface::new(spi);
+/// let mut display = Ssd1331::from_interface(interface, Rotate0, [0; BUF_SIZE]);
+///
+/// display.init().unwrap();
+/// display.flush().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PackedThreeWireInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> PackedThreeWireInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    /// Create a new packed 3 wire interface from an 8 bit SPI device
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Release the SPI device for reuse in other code
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    /// Send bytes with the given D/C flag
+    fn send(&mut self, dc: bool, words: DataFormat<'_>) -> Result<(), DisplayError> {
+        let flag = u32::from(dc) << 8;
+        let mut chunk = [0u8; CHUNK_WORDS * 9 / 8];
+        let mut len = 0;
+        let mut words_in_chunk = 0;
+
+        // Bits not yet written to the chunk, aligned to the LSB
+        let mut bits = 0u32;
+        let mut num_bits = 0;
+
+        for_each_byte(words, |byte| {
+            bits = (bits << 9) | flag | u32::from(byte);
+            num_bits += 9;
+
+            while num_bits >= 8 {
+                num_bits -= 8;
+                chunk[len] = (bits >> num_bits) as u8;
+                len += 1;
+            }
+
+            bits &= (1 << num_bits) - 1;
+            words_in_chunk += 1;
+
+            // Always a whole number of bytes, so there are no leftover bits
+            if words_in_chunk == CHUNK_WORDS {
+                words_in_chunk = 0;
+                len = 0;
+                self.spi
+                    .write(&chunk)
+                    .map_err(|_| DisplayError::BusWriteError)?;
+            }
+
+            Ok(())
+        })?;
+
+        if num_bits > 0 {
+            chunk[len] = (bits << (8 - num_bits)) as u8;
+            len += 1;
+        }
+
+        if len > 0 {
+            self.spi
+                .write(&chunk[0..len])
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<SPI> WriteOnlyDataCommand for PackedThreeWireInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(false, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(true, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::spi::{ErrorType, Operation};
+    use std::{vec, vec::Vec};
+
+    /// SPI device which records the words of each write
+    #[derive(Default)]
+    struct RecordingSpi<W> {
+        writes: Vec<Vec<W>>,
+    }
+
+    impl<W> ErrorType for RecordingSpi<W> {
+        type Error = Infallible;
+    }
+
+    impl<W: Copy + 'static> SpiDevice<W> for RecordingSpi<W> {
+        fn transaction(&mut self, operations: &mut [Operation<'_, W>]) -> Result<(), Infallible> {
+            for op in operations {
+                if let Operation::Write(words) = op {
+                    self.writes.push(words.to_vec());
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    fn packed_command(bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut iface = PackedThreeWireInterface::new(RecordingSpi::default());
+        iface.send_commands(DataFormat::U8(bytes)).unwrap();
+
+        iface.release().writes
+    }
+
+    fn packed_data(bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut iface = PackedThreeWireInterface::new(RecordingSpi::default());
+        iface.send_data(DataFormat::U8(bytes)).unwrap();
+
+        iface.release().writes
+    }
+
+    #[test]
+    fn packed_command_is_padded() {
+        assert_eq!(packed_command(&[0xAF]), vec![vec![0x57, 0x80]]);
+    }
+
+    #[test]
+    fn packed_data_sets_dc_bit() {
+        assert_eq!(
+            packed_data(&[0xFF, 0x00, 0x81]),
+            vec![vec![0xFF, 0xC0, 0x30, 0x20]]
+        );
+    }
+
+    #[test]
+    fn packed_multiple_chunks() {
+        // 8 data words of 0x00 pack into 9 bytes with the D/C bit moving along by one each byte
+        let group = [0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00];
+        let full_chunk: Vec<u8> = group.iter().cycle().take(36).copied().collect();
+
+        assert_eq!(
+            packed_data(&[0x00; CHUNK_WORDS + 1]),
+            vec![full_chunk, vec![0x80, 0x00]]
+        );
+    }
+
+    #[test]
+    fn packed_exact_chunk_has_no_tail() {
+        assert_eq!(packed_data(&[0x00; CHUNK_WORDS]).len(), 1);
+    }
+
+    #[test]
+    fn nine_bit_words() {
+        let mut iface = ThreeWireInterface::new(RecordingSpi::default());
+        iface.send_commands(DataFormat::U8(&[0xAF])).unwrap();
+        iface
+            .send_data(DataFormat::U8(&[0x81; CHUNK_WORDS + 1]))
+            .unwrap();
+
+        assert_eq!(
+            iface.release().writes,
+            vec![vec![0x0AF], vec![0x181; CHUNK_WORDS], vec![0x181],]
+        );
+    }
+}
@@ -0,0 +1,448 @@
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use display_interface_spi::SPIInterface;
+use hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
+
+use crate::{
+    command::{ColorMode, Command},
+    display,
+    displayrotation::DisplayRotation,
+    error::Error,
+};
+
+/// Number of bytes sent to the display in each write when streaming pixels
+const CHUNK_SIZE: usize = 64;
+
+/// SSD1331 display interface without a framebuffer
+///
+/// Unlike [`Ssd1331`], this driver does not store a copy of the display contents in memory.
+/// Everything drawn is sent straight to the display, so no call to `flush()` is required. This
+/// saves the 12,288 bytes of memory used by the framebuffer at the cost of drawing speed, as each
+/// individual pixel drawn requires a command to select its position on the display. Drawing whole
+/// areas at once with [`write_pixels`] or `embedded-graphics`' `fill_solid` and
+/// `fill_contiguous` avoids most of this overhead.
+///
+/// [`Ssd1331`]: ./struct.Ssd1331.html
+/// [`write_pixels`]: #method.write_pixels
+///
+/// # Examples
+///
+/// ## Draw a filled rectangle with [`embedded-graphics`]
+///
+/// This requires the `graphics` feature to be enabled (on by default).
+///
+/// ```rust
+/// use embedded_graphics::{
+///     pixelcolor::Rgb565,
+///     prelude::*,
+///     primitives::{PrimitiveStyle, Rectangle},
+/// };
+/// use ssd1331::{DisplayRotation::Rotate0, UnbufferedSsd1331};
+/// # use ssd1331::test_helpers::{Pin, Spi};
+///
+/// // Set up SPI interface and digital pin. These are stub implementations used in examples.
+/// let spi = Spi;
+/// let dc = Pin;
+///
+/// let mut display = UnbufferedSsd1331::new(spi, dc, Rotate0);
+///
+/// // Initialise and clear the display
+/// display.init().unwrap();
+/// display.clear(Rgb565::BLACK).unwrap();
+///
+/// // Drawn directly to the display
+/// Rectangle::new(Point::new(8, 8), Size::new(32, 16))
+///     .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+///
+/// [`embedded-graphics`]: https://crates.io/crates/embedded-graphics
+pub struct UnbufferedSsd1331<DI> {
+    /// Which display rotation to use
+    display_rotation: DisplayRotation,
+
+    /// Display interface
+    iface: DI,
+}
+
+impl<SPI, DC> UnbufferedSsd1331<SPIInterface<SPI, DC>>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Create new display instance
+    ///
+    /// Ensure `display.init()` is called before sending data otherwise nothing will be shown.
+    pub fn new(spi: SPI, dc: DC, display_rotation: DisplayRotation) -> Self {
+        Self::from_interface(SPIInterface::new(spi, dc), display_rotation)
+    }
+}
+
+impl<DI> UnbufferedSsd1331<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    /// Create new display instance using the given display interface
+    ///
+    /// See [`Ssd1331::from_interface`] for details.
+    ///
+    /// [`Ssd1331::from_interface`]: ./struct.Ssd1331.html#method.from_interface
+    pub fn from_interface(iface: DI, display_rotation: DisplayRotation) -> Self {
+        Self {
+            iface,
+            display_rotation,
+        }
+    }
+
+    /// Release the display interface for reuse in other code
+    pub fn release(self) -> DI {
+        self.iface
+    }
+
+    /// Reset the display
+    ///
+    /// This method brings the RST pin low for 1ms to reset the module,  waits for another 1ms then
+    /// brings RST high
+    pub fn reset<RST, DELAY, PinE>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<(), PinE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DELAY: DelayNs,
+    {
+        display::reset(rst, delay)
+    }
+
+    /// Initialise display, setting sensible defaults and rotation
+    ///
+    /// The contents of the display are undefined after initialisation, so it should be cleared
+    /// before drawing.
+    pub fn init(&mut self) -> Result<(), DisplayError> {
+        for command in Command::init_sequence(self.display_rotation, ColorMode::CM65k) {
+            command.send(&mut self.iface)?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the top left and bottom right corners of a bounding box to draw to
+    pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
+        Command::ColumnAddress(start.0, end.0).send(&mut self.iface)?;
+        Command::RowAddress(start.1, end.1).send(&mut self.iface)?;
+        Ok(())
+    }
+
+    /// Set the value for an individual pixel on the display
+    ///
+    /// Pixels outside the display are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u16) -> Result<(), DisplayError> {
+        let (w, h) = self.dimensions();
+
+        if x >= u32::from(w) || y >= u32::from(h) {
+            return Ok(());
+        }
+
+        let point = (x as u8, y as u8);
+
+        self.write_pixels(point, point, core::iter::once(value))
+    }
+
+    /// Write pixels to a rectangular area of the display
+    ///
+    /// `start` and `end` are opposite corners of the area, given in the same rotated coordinate
+    /// space as [`set_pixel`]. `pixels` are raw RGB565 values, ordered row by row from the top left
+    /// corner of the area. Pixels beyond the size of the area wrap back around to the top left
+    /// corner.
+    ///
+    /// Nothing is written if either corner lies outside the display.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    pub fn write_pixels<I>(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+        pixels: I,
+    ) -> Result<(), DisplayError>
+    where
+        I: IntoIterator<Item = u16>,
+    {
+        let (gram_start, gram_end) = match self.display_rotation.gram_area(start, end) {
+            Some(area) => area,
+            None => return Ok(()),
+        };
+
+        self.set_draw_area(gram_start, gram_end)?;
+
+        let mut chunk = [0u8; CHUNK_SIZE];
+        let mut len = 0;
+
+        for pixel in pixels {
+            chunk[len..len + 2].copy_from_slice(&pixel.to_be_bytes());
+            len += 2;
+
+            if len == CHUNK_SIZE {
+                self.iface.send_data(DataFormat::U8(&chunk))?;
+                len = 0;
+            }
+        }
+
+        self.iface.send_data(DataFormat::U8(&chunk[0..len]))
+    }
+
+    /// Fill a rectangular area of the display with a single colour
+    ///
+    /// `start` and `end` are opposite corners of the area, given in the same rotated coordinate
+    /// space as [`set_pixel`].
+    ///
+    /// Nothing is filled if either corner lies outside the display.
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    pub fn fill_area(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+        value: u16,
+    ) -> Result<(), DisplayError> {
+        let width = usize::from(start.0.max(end.0) - start.0.min(end.0)) + 1;
+        let height = usize::from(start.1.max(end.1) - start.1.min(end.1)) + 1;
+
+        self.write_pixels(start, end, core::iter::repeat(value).take(width * height))
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn dimensions(&self) -> (u8, u8) {
+        self.display_rotation.dimensions()
+    }
+
+    /// Set the display rotation
+    ///
+    /// Anything already shown on the display is not redrawn in the new orientation.
+    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DisplayError> {
+        self.display_rotation = rot;
+
+        rot.remap_command(ColorMode::CM65k).send(&mut self.iface)
+    }
+
+    /// Get the current rotation of the display
+    pub fn rotation(&self) -> DisplayRotation {
+        self.display_rotation
+    }
+
+    /// Turn the display on (eg exiting sleep mode)
+    pub fn turn_on(&mut self) -> Result<(), DisplayError> {
+        Command::DisplayOn(true).send(&mut self.iface)
+    }
+
+    /// Turn the display off (enter sleep mode)
+    pub fn turn_off(&mut self) -> Result<(), DisplayError> {
+        Command::DisplayOn(false).send(&mut self.iface)
+    }
+}
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::{
+        raw::{RawData, RawU16},
+        Rgb565,
+    },
+    primitives::Rectangle,
+    Pixel,
+};
+
+#[cfg(feature = "graphics")]
+use crate::framebuffer::{corners, skip};
+
+#[cfg(feature = "graphics")]
+impl<DI> DrawTarget for UnbufferedSsd1331<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    type Color = Rgb565;
+    type Error = DisplayError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .try_for_each(|Pixel(pos, color)| {
+                self.set_pixel(pos.x as u32, pos.y as u32, RawU16::from(color).into_inner())
+            })
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable = area.intersection(&self.bounding_box());
+
+        let (start, end) = match corners(&drawable) {
+            Some(corners) => corners,
+            None => return Ok(()),
+        };
+
+        // Number of colours to skip before, inside and after the drawable part of each row
+        let area_width = area.size.width as usize;
+        let skip_left = (drawable.top_left.x - area.top_left.x) as usize;
+        let width = drawable.size.width as usize;
+        let skip_right = area_width - skip_left - width;
+
+        let mut colors = colors.into_iter();
+
+        skip(
+            &mut colors,
+            (drawable.top_left.y - area.top_left.y) as usize * area_width + skip_left,
+        );
+
+        let mut column = 0;
+
+        let pixels = core::iter::from_fn(move || {
+            // Skip the clipped colours between the end of one row and the start of the next
+            if column == width {
+                skip(&mut colors, skip_right + skip_left);
+                column = 0;
+            }
+
+            column += 1;
+
+            colors.next()
+        })
+        .take(width * drawable.size.height as usize)
+        .map(|color| RawU16::from(color).into_inner());
+
+        self.write_pixels(start, end, pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let drawable = area.intersection(&self.bounding_box());
+
+        match corners(&drawable) {
+            Some((start, end)) => self.fill_area(start, end, RawU16::from(color).into_inner()),
+            None => Ok(()),
+        }
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI> OriginDimensions for UnbufferedSsd1331<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    fn size(&self) -> Size {
+        let (w, h) = self.dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_graphics_core::geometry::Point;
+    use std::{vec, vec::Vec};
+
+    /// Display interface which records the bytes sent in data mode
+    #[derive(Default)]
+    struct RecordingInterface {
+        data: Vec<u8>,
+    }
+
+    impl WriteOnlyDataCommand for RecordingInterface {
+        fn send_commands(&mut self, _cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            Ok(())
+        }
+
+        fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            match buf {
+                DataFormat::U8(bytes) => self.data.extend_from_slice(bytes),
+                _ => return Err(DisplayError::DataFormatNotImplemented),
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Fill an area of the given size at `top_left` with colours numbered from 1 along each row,
+    /// returning the colours sent to the display
+    fn fill_contiguous(
+        rotation: DisplayRotation,
+        top_left: (i32, i32),
+        size: (u32, u32),
+    ) -> Vec<u16> {
+        let mut display =
+            UnbufferedSsd1331::from_interface(RecordingInterface::default(), rotation);
+
+        let area = Rectangle::new(
+            Point::new(top_left.0, top_left.1),
+            Size::new(size.0, size.1),
+        );
+        let colors = (1..=(size.0 * size.1) as u16).map(|value| Rgb565::from(RawU16::new(value)));
+
+        display.fill_contiguous(&area, colors).unwrap();
+
+        display
+            .iface
+            .data
+            .chunks_exact(2)
+            .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn fill_contiguous_unclipped() {
+        assert_eq!(
+            fill_contiguous(DisplayRotation::Rotate0, (10, 10), (4, 3)),
+            (1..=12).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fill_contiguous_clipped_top_left() {
+        assert_eq!(
+            fill_contiguous(DisplayRotation::Rotate0, (-2, -1), (4, 3)),
+            vec![7, 8, 11, 12]
+        );
+    }
+
+    #[test]
+    fn fill_contiguous_clipped_bottom_right() {
+        assert_eq!(
+            fill_contiguous(DisplayRotation::Rotate0, (93, 62), (4, 3)),
+            vec![1, 2, 3, 5, 6, 7]
+        );
+        assert_eq!(
+            fill_contiguous(DisplayRotation::Rotate90, (61, 94), (4, 3)),
+            vec![1, 2, 3, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn fill_contiguous_clipped_left_and_right() {
+        assert_eq!(
+            fill_contiguous(DisplayRotation::Rotate0, (-2, 0), (100, 2)),
+            (3..=98).chain(103..=198).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fill_contiguous_outside() {
+        assert_eq!(
+            fill_contiguous(DisplayRotation::Rotate0, (96, 0), (4, 3)),
+            vec![]
+        );
+    }
+}